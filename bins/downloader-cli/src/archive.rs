@@ -0,0 +1,161 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use app_actions::canonical_url::canonicalize_url;
+use app_helpers::encoding::to_base64;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+use tracing::{debug, warn};
+use url::Url;
+
+/// A file with one JSON entry per line for every file that was downloaded and fixed.
+#[derive(Debug)]
+pub struct DownloadArchive {
+    path: PathBuf,
+    entries: HashMap<String, Vec<ArchiveEntry>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    /// The canonical URL the file was downloaded from
+    pub url: String,
+    /// Hash of the fixed file
+    pub hash: String,
+    /// Path of the fixed file
+    pub path: PathBuf,
+}
+
+impl DownloadArchive {
+    pub async fn load(path: &Path) -> Result<Self, String> {
+        let mut archive = Self {
+            path: path.to_path_buf(),
+            entries: HashMap::new(),
+        };
+
+        let contents = match tokio::fs::read_to_string(path).await {
+            Ok(x) => x,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(archive),
+            Err(e) => return Err(format!("Failed to read download archive: {e:?}")),
+        };
+
+        for (i, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str::<ArchiveEntry>(line) {
+                Ok(entry) => archive
+                    .entries
+                    .entry(entry.url.clone())
+                    .or_default()
+                    .push(entry),
+                Err(e) => warn!("Skipping invalid download archive line {}: {e}", i + 1),
+            }
+        }
+
+        debug!(count = archive.entries.len(), "Loaded download archive");
+
+        Ok(archive)
+    }
+
+    /// Whether the URL was already downloaded.
+    ///
+    /// If `require_files` is set, the URL only counts as downloaded
+    /// while all the files it resulted in still exist.
+    pub fn contains(&self, url: &Url, require_files: bool) -> bool {
+        self.entries
+            .get(canonicalize_url(url).as_str())
+            .is_some_and(|entries| !require_files || entries.iter().all(|x| x.path.exists()))
+    }
+
+    /// Records the files the URL resulted in.
+    ///
+    /// Replaces any previous entries of the URL (eg. with `--redownload-missing`),
+    /// in which case the whole file is rewritten without them.
+    pub async fn set(&mut self, url: &Url, file_paths: &[PathBuf]) -> Result<(), String> {
+        let url = canonicalize_url(url).to_string();
+
+        let mut entries = vec![];
+        for file_path in file_paths {
+            entries.push(ArchiveEntry {
+                url: url.clone(),
+                hash: file_hash(file_path).await?,
+                path: file_path.clone(),
+            });
+        }
+
+        let replaced = self.entries.insert(url, entries.clone()).is_some();
+        if replaced {
+            self.rewrite().await
+        } else {
+            self.append(&entries).await
+        }
+    }
+
+    async fn append(&self, entries: &[ArchiveEntry]) -> Result<(), String> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|e| format!("Failed to open download archive: {e:?}"))?
+            .write_all(serialize_entries(entries)?.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write to download archive: {e:?}"))
+    }
+
+    /// Writes all the entries to a temporary file which then replaces the archive
+    async fn rewrite(&self) -> Result<(), String> {
+        let entries = self.entries.values().flatten().cloned().collect::<Vec<_>>();
+
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+        let temp_path = PathBuf::from(temp_path);
+
+        tokio::fs::write(&temp_path, serialize_entries(&entries)?)
+            .await
+            .map_err(|e| format!("Failed to write download archive: {e:?}"))?;
+
+        tokio::fs::rename(&temp_path, &self.path)
+            .await
+            .map_err(|e| format!("Failed to replace download archive: {e:?}"))
+    }
+}
+
+fn serialize_entries(entries: &[ArchiveEntry]) -> Result<String, String> {
+    let mut lines = String::new();
+
+    for entry in entries {
+        lines.push_str(
+            &serde_json::to_string(entry)
+                .map_err(|e| format!("Failed to serialize archive entry: {e:?}"))?,
+        );
+        lines.push('\n');
+    }
+
+    Ok(lines)
+}
+
+pub async fn file_hash(file: &Path) -> Result<String, String> {
+    let file = file.to_path_buf();
+
+    tokio::task::spawn_blocking(move || {
+        let input =
+            std::fs::File::open(&file).map_err(|e| format!("Failed to open file: {e:?}"))?;
+        let mut reader = std::io::BufReader::new(input);
+        let mut hasher = sha2::Sha384::new();
+
+        std::io::copy(&mut reader, &mut hasher)
+            .map_err(|e| format!("Failed to hash file: {e:?}"))?;
+
+        Ok(format!(
+            "sha384:{digest}",
+            digest = to_base64(hasher.finalize())
+        ))
+    })
+    .await
+    .map_err(|e| format!("Failed to hash file: {e:?}"))?
+}
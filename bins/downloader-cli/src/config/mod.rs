@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use app_actions::actions::ActionOptions;
 use app_config::{
     common::{self},
     validators::{
@@ -9,27 +10,46 @@ use app_config::{
     },
     Dumpable, GlobalConfig,
 };
-use clap::{Args, Parser, ValueHint};
+use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use url::Url;
+use validator::{Validate, ValidationErrors};
 
 #[derive(
     Debug, Default, Clone, Serialize, Deserialize, Parser, Validate, GlobalConfig, Dumpable,
 )]
+#[command(subcommand_negates_reqs = true)]
 pub struct Config {
     #[clap(flatten)]
     #[validate(nested)]
     #[serde(skip)]
     pub run: RunConfig,
 
+    #[command(subcommand)]
+    #[validate(nested)]
+    #[serde(skip)]
+    pub command: Option<Command>,
+
     #[clap(flatten)]
     #[validate(nested)]
     pub endpoint: common::EndpointConfig,
 
+    #[clap(flatten)]
+    #[validate(nested)]
+    pub credentials: common::CredentialsConfig,
+
+    #[clap(flatten)]
+    #[validate(nested)]
+    pub proxy: common::ProxyConfig,
+
     #[clap(flatten)]
     #[validate(nested)]
     pub dependency_paths: common::ProgramPathConfig,
 
+    #[clap(flatten)]
+    #[validate(nested)]
+    pub hub: HubConfig,
+
     #[clap(flatten)]
     #[validate(nested)]
     #[serde(skip)]
@@ -45,7 +65,12 @@ impl Config {
 
         {
             let parsed = parsed.clone();
-            app_actions::config::init(parsed.endpoint, parsed.dependency_paths)?;
+            app_actions::config::init(
+                parsed.endpoint,
+                parsed.dependency_paths,
+                parsed.credentials,
+                parsed.proxy,
+            )?;
         }
 
         Self::init(parsed)
@@ -63,17 +88,63 @@ impl Config {
 
     #[inline]
     fn validate_or_exit(self) -> Self {
-        if let Err(e) = self.validate() {
-            eprintln!("Errors validating configuration:");
-            print_validation_errors(&e, "  ", 1);
-            std::process::exit(1);
+        if let Err(mut e) = self.validate() {
+            // The hub downloads and fixes the files, so the programs aren't needed locally
+            if self.hub.is_enabled() {
+                e.errors_mut().remove("dependency_paths");
+            }
+
+            if !e.is_empty() {
+                eprintln!("Errors validating configuration:");
+                print_validation_errors(&e, "  ", 1);
+                std::process::exit(1);
+            }
         }
 
         self
     }
 }
 
+const DEFAULT_HUB_POLL_INTERVAL_SECONDS: u64 = 2;
+const DEFAULT_HUB_MAX_WAIT_SECONDS: u64 = 60 * 60;
+
+#[derive(Debug, Clone, Default, Args, Serialize, Deserialize, Validate)]
+#[clap(next_help_heading = Some("Remote hub"))]
+pub struct HubConfig {
+    /// Have a downloader hub server download the URLs instead of downloading them locally.
+    ///
+    /// The hub downloads and fixes the files which are then downloaded into the output directory,
+    /// so none of the programs have to be installed locally.
+    /// Local files and actions are not supported when using a hub.
+    #[arg(long, env = "DOWNLOADER_HUB_URL", value_hint = ValueHint::Url, requires = "api_key", conflicts_with_all = ["files", "split_files", "actions"])]
+    pub hub_url: Option<Url>,
+
+    /// API key of the client to use on the hub
+    #[arg(long, env = "DOWNLOADER_HUB_API_KEY", requires = "hub_url")]
+    #[serde(skip_serializing)]
+    pub api_key: Option<String>,
+
+    /// How many seconds to wait between checking the status of the hub requests
+    #[arg(long, default_value_t = DEFAULT_HUB_POLL_INTERVAL_SECONDS)]
+    #[validate(range(min = 1))]
+    pub hub_poll_interval: u64,
+
+    /// How many seconds to wait for the hub to finish a request before giving up on its URL
+    #[arg(long, default_value_t = DEFAULT_HUB_MAX_WAIT_SECONDS)]
+    #[validate(range(min = 1))]
+    pub hub_max_wait: u64,
+}
+impl HubConfig {
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.hub_url.is_some()
+    }
+}
+
+const DEFAULT_CONCURRENCY: usize = 4;
+
 #[derive(Debug, Clone, Default, Args, Serialize, Deserialize, Validate)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct RunConfig {
     #[clap(flatten)]
     #[validate(nested)]
@@ -97,6 +168,204 @@ pub struct RunConfig {
     /// The standard format is `<id>.<original_name>.<extension>`.
     #[clap(long, action = clap::ArgAction::SetTrue)]
     pub and_rename: bool,
+
+    /// Write the metadata of the post the files were downloaded from to a JSON sidecar file.
+    ///
+    /// The sidecar is written next to each downloaded file as `<file_name>.json`.
+    /// Nothing is written if the extractor didn't provide any post metadata.
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    pub write_metadata: bool,
+
+    /// Render downloaded photo slideshows into a video with their sound.
+    ///
+    /// The video is saved next to the downloaded images and audio track.
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    pub render_slideshows: bool,
+
+    /// Keep track of downloaded URLs in this file and skip the ones that are already in it.
+    ///
+    /// Stores the canonical URL of every download along with the path and hash of the fixed file,
+    /// one JSON object per line.
+    /// URLs are only added once all of their files were downloaded and fixed.
+    /// Will be created if it doesn't exist.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub download_archive: Option<PathBuf>,
+
+    /// Download and fix URLs from the download archive again if their files no longer exist.
+    #[clap(long, action = clap::ArgAction::SetTrue, requires = "download_archive")]
+    pub redownload_missing: bool,
+
+    /// How many URLs to download and files to fix at the same time.
+    #[clap(short = 'j', long, default_value_t = DEFAULT_CONCURRENCY)]
+    #[validate(range(min = 1))]
+    pub concurrency: usize,
+
+    /// Stop at the first download or file that fails instead of processing the rest.
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    pub fail_fast: bool,
+
+    /// Format of the results printed to stdout.
+    ///
+    /// `json` prints one JSON object per input (URL or file) on its own line
+    /// and sends all logs to stderr.
+    ///
+    /// Exits with `0` if every input succeeded, `2` if only some of them failed
+    /// and `1` if all of them failed.
+    /// Inputs that were never run because of `--fail-fast` are reported as `not_run`
+    /// and don't count as succeeded.
+    #[clap(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub output_format: OutputFormat,
+
+    /// Run an action on every fixed file.
+    ///
+    /// Format: `<name>[:<key>=<value>,...]`, eg. `CompactMedia` or `OcrImage:engine="2"`.
+    /// Values are parsed as JSON and fall back to plain strings, so numbers have to be quoted
+    /// to be passed as strings. Lists are passed as JSON arrays,
+    /// eg. `RenderSlideshow:images=["a.jpg","b.jpg"],seconds-per-image=3`.
+    /// A key without a value is `true`.
+    /// Can be repeated and the actions run in the order they are given.
+    ///
+    /// Text results are printed to stdout and files are written to the output directory.
+    #[clap(long = "action", value_parser = ActionSpec::parse_str)]
+    pub actions: Vec<ActionSpec>,
+
+    /// Print what would be done with the entries without downloading or changing anything.
+    ///
+    /// Shows the extractor that matches each URL, the URLs it finds with the downloaders
+    /// that would download them and which fixers would run for each file.
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    pub explain: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionSpec {
+    pub name: String,
+    pub options: ActionOptions,
+}
+impl ActionSpec {
+    pub fn parse_str(arg: &str) -> Result<Self, String> {
+        let (name, options) = arg.split_once(':').unwrap_or((arg, ""));
+        let name = name.trim();
+
+        if name.is_empty() {
+            return Err(format!("invalid action (missing name): {arg}"));
+        }
+
+        let options = split_action_options(options)
+            .into_iter()
+            .filter_map(parse_action_option)
+            .collect::<ActionOptions>();
+
+        Ok(Self {
+            name: name.to_string(),
+            options,
+        })
+    }
+}
+
+/// Splits the options on the commas that are not inside a JSON array, object or string
+fn split_action_options(s: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0_usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (i, c) in s.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_string = true,
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+
+    parts
+}
+
+fn parse_action_option(s: &str) -> Option<(String, serde_json::Value)> {
+    let (k, v) = s.split_once('=').unwrap_or((s, ""));
+    let (k, v) = (k.trim(), v.trim());
+
+    if k.is_empty() {
+        return None;
+    }
+
+    let v = if v.is_empty() {
+        true.into()
+    } else {
+        serde_json::from_str(v).unwrap_or_else(|_| v.into())
+    };
+
+    Some((k.to_string(), v))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Human readable logs only
+    #[default]
+    Text,
+    /// One JSON object per input
+    Json,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Watch a directory and fix files as they are added to it.
+    ///
+    /// Uses the output directory and actions from the main options.
+    /// `.url` and `.txt` files are read for links which are downloaded into the output directory.
+    Watch(WatchConfig),
+}
+impl Validate for Command {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        match self {
+            Self::Watch(x) => x.validate(),
+        }
+    }
+}
+
+const DEFAULT_SETTLE_SECONDS: u64 = 2;
+
+#[derive(Debug, Clone, Args, Validate)]
+pub struct WatchConfig {
+    /// Directory to watch for new files
+    #[clap(value_hint = ValueHint::DirPath, value_parser = value_parser_parse_valid_directory())]
+    #[validate(custom(function = "validate_is_writable_directory"))]
+    pub directory: PathBuf,
+
+    /// Also watch the subdirectories of the directory
+    #[clap(short = 'r', long, action = clap::ArgAction::SetTrue)]
+    pub recursive: bool,
+
+    /// How many seconds a file has to stay unchanged before it is processed.
+    ///
+    /// Gives other programs time to finish writing the file.
+    #[clap(long, default_value_t = DEFAULT_SETTLE_SECONDS)]
+    pub settle_seconds: u64,
+
+    /// File to keep track of the processed files in.
+    ///
+    /// Files are matched by their contents, so renamed or moved files are not processed again.
+    /// Defaults to `.downloader-cli-watch.jsonl` in the watched directory.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub state_file: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Default, Args, Serialize, Deserialize, Validate)]
@@ -139,6 +408,14 @@ pub struct UrlGroup {
     /// Invalid entries will be _ignored_.
     #[clap(id = "URL_OR_FILE", value_hint = ValueHint::FilePath)]
     pub urls_or_files: Vec<DownloadEntry>,
+
+    /// Read entries from a file with one entry per line.
+    ///
+    /// Use `-` to read from stdin.
+    /// Entries have the same behaviour as the raw arguments.
+    /// Empty lines and lines starting with `#` or `;` are ignored.
+    #[clap(short = 'b', long = "batch-file", value_hint = ValueHint::FilePath)]
+    pub batch_files: Vec<String>,
 }
 
 pub type DownloadEntry = String;
@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+
+use app_actions::explain::{explain_file, explain_url, DownloadPlan, FixPlan};
+use serde::Serialize;
+use url::Url;
+
+use crate::config::{Config, OutputFormat};
+
+#[derive(Debug, Default, Serialize)]
+struct ExplainRecord {
+    source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    download_plan: Option<DownloadPlan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix_plan: Option<FixPlan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Prints the plan for every URL and file.
+///
+/// Returns the exit code, with the same meaning as for a normal run.
+pub async fn run(urls: &[Url], files: &[PathBuf]) -> i32 {
+    let mut records = vec![];
+
+    for url in urls {
+        let plan = explain_url(url, &Config::run().output_directory).await;
+
+        records.push(match plan {
+            Ok(x) => ExplainRecord {
+                source: url.to_string(),
+                download_plan: Some(x),
+                ..Default::default()
+            },
+            Err(e) => ExplainRecord {
+                source: url.to_string(),
+                error: Some(e),
+                ..Default::default()
+            },
+        });
+    }
+
+    for file in files {
+        let plan = explain_file(file).await;
+
+        records.push(match plan {
+            Ok(x) => ExplainRecord {
+                source: file.display().to_string(),
+                fix_plan: Some(x),
+                ..Default::default()
+            },
+            Err(e) => ExplainRecord {
+                source: file.display().to_string(),
+                error: Some(e),
+                ..Default::default()
+            },
+        });
+    }
+
+    for record in &records {
+        match Config::run().output_format {
+            OutputFormat::Json => match serde_json::to_string(record) {
+                Ok(x) => println!("{x}"),
+                Err(e) => eprintln!("Failed to serialize plan for {:?}: {e:?}", record.source),
+            },
+            OutputFormat::Text => print_record(record),
+        }
+    }
+
+    let failed = records.iter().filter(|x| x.error.is_some()).count();
+    if failed == 0 {
+        0
+    } else if failed == records.len() {
+        1
+    } else {
+        2
+    }
+}
+
+fn print_record(record: &ExplainRecord) {
+    println!("{}", record.source);
+
+    if let Some(e) = &record.error {
+        println!("  Error: {e}");
+    }
+
+    if let Some(plan) = &record.download_plan {
+        println!(
+            "  Extractor: {}",
+            plan.extractor.as_deref().unwrap_or("<unknown>")
+        );
+
+        if plan.downloads.is_empty() {
+            println!("  No URLs to download");
+        }
+
+        for download in &plan.downloads {
+            println!("  {}", download.url);
+            println!(
+                "    Downloader: {}",
+                download
+                    .downloader
+                    .as_deref()
+                    .unwrap_or("<none can download this URL>")
+            );
+
+            if let Some(preferred) = &download.preferred_downloader {
+                println!("    Preferred downloader: {preferred}");
+            }
+
+            if !download.downloader_options.is_empty() {
+                println!(
+                    "    Options: {}",
+                    serde_json::to_string(&download.downloader_options).unwrap_or_default()
+                );
+            }
+        }
+    }
+
+    if let Some(plan) = &record.fix_plan {
+        println!("  Fixers:");
+
+        for fixer in &plan.fixers {
+            println!(
+                "    [{}] {}",
+                if fixer.would_run { "x" } else { " " },
+                fixer.name
+            );
+        }
+    }
+}
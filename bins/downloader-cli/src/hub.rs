@@ -0,0 +1,285 @@
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use app_actions::{
+    downloaders::{
+        self, handlers::generic::Generic, DownloadRequest, DownloadResult, DownloaderEntry,
+        MediaInfo,
+    },
+    extractors::PostMetadata,
+};
+use reqwest::{header, Method, RequestBuilder};
+use serde::{de::DeserializeOwned, Deserialize};
+use tracing::{debug, info};
+use url::Url;
+
+use crate::config::HubConfig;
+
+const TIMEOUT_SECS: u64 = 30;
+
+/// Client for a downloader hub server.
+///
+/// The hub does the downloading and fixing, the client only fetches the finished files.
+#[derive(Debug, Clone)]
+pub struct HubClient {
+    base_url: Url,
+    api_key: String,
+    poll_interval: Duration,
+    max_wait: Duration,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiResponse<T> {
+    body: ApiResponseBody<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", content = "data")]
+#[serde(rename_all = "camelCase")]
+enum ApiResponseBody<T> {
+    Empty,
+    Success(T),
+    Error(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+enum HubItemStatus {
+    Failed,
+    Pending,
+    Processing,
+    Success,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HubRequest {
+    request_uid: String,
+    status: HubItemStatus,
+    #[serde(default)]
+    post_metadata: Option<PostMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HubRequestInfo {
+    request: HubRequest,
+    results: Vec<HubResult>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HubResult {
+    result_uid: String,
+    status: HubItemStatus,
+    #[serde(default)]
+    meta: serde_json::Value,
+    download_url: Option<Url>,
+}
+
+/// The extractor and downloader the hub used for a result
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct HubResultSource {
+    extractor: Option<String>,
+    downloader: Option<String>,
+}
+
+impl HubClient {
+    /// Creates the client if a hub is configured
+    pub fn from_config(config: &HubConfig) -> Result<Option<Self>, String> {
+        let Some(mut base_url) = config.hub_url.clone() else {
+            return Ok(None);
+        };
+        // So the endpoints get joined onto the path instead of replacing the last segment
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(TIMEOUT_SECS))
+            .timeout(Duration::from_secs(TIMEOUT_SECS))
+            .build()
+            .map_err(|e| format!("Failed to create hub client: {e:?}"))?;
+
+        Ok(Some(Self {
+            base_url,
+            api_key: config.api_key.clone().unwrap_or_default(),
+            poll_interval: Duration::from_secs(config.hub_poll_interval),
+            max_wait: Duration::from_secs(config.hub_max_wait),
+            client,
+        }))
+    }
+
+    /// Has the hub download the URL and downloads the resulting files into `download_dir`.
+    ///
+    /// Returns one result per file, same as a local download would.
+    pub async fn download(
+        &self,
+        url: &Url,
+        download_dir: &Path,
+    ) -> Vec<Result<DownloadResult, String>> {
+        let request_uid = match self.submit(url).await {
+            Ok(x) => x,
+            Err(e) => return vec![Err(e)],
+        };
+
+        let info = match self.wait_for(&request_uid).await {
+            Ok(x) => x,
+            Err(e) => return vec![Err(e)],
+        };
+
+        if info.request.status == HubItemStatus::Failed && info.results.is_empty() {
+            return vec![Err(format!(
+                "Hub failed to process request {request_uid:?}"
+            ))];
+        }
+
+        let downloader: DownloaderEntry = Arc::new(Generic);
+        let mut results = vec![];
+        let post_metadata = info.request.post_metadata;
+        for result in info.results {
+            let download_url = match (result.status, result.download_url) {
+                (HubItemStatus::Success, Some(x)) => x,
+                (_, _) => {
+                    let reason = result
+                        .meta
+                        .get("error")
+                        .and_then(|x| x.as_str())
+                        .unwrap_or("unknown error");
+
+                    results.push(Err(format!(
+                        "Hub failed to download result {:?}: {reason}",
+                        result.result_uid
+                    )));
+                    continue;
+                }
+            };
+
+            debug!(result = ?result.result_uid, "Downloading result from hub");
+            let request = DownloadRequest::from_url(download_url.as_str(), download_dir);
+            match downloaders::download_file_with(std::slice::from_ref(&downloader), &request).await
+            {
+                Ok(x) => {
+                    // Report the result as if it was downloaded locally
+                    let source = result
+                        .meta
+                        .get("source")
+                        .and_then(|x| HubResultSource::deserialize(x).ok())
+                        .unwrap_or_default();
+                    let media_info = result
+                        .meta
+                        .get("mediaInfo")
+                        .and_then(|x| MediaInfo::deserialize(x).ok());
+
+                    results.extend(x.into_iter().map(|mut x| {
+                        x.request.extractor.clone_from(&source.extractor);
+                        x.request.post_metadata.clone_from(&post_metadata);
+                        x.downloader.clone_from(&source.downloader);
+                        x.media_info.clone_from(&media_info);
+                        Ok(x)
+                    }));
+                }
+                Err(e) => results.push(Err(format!(
+                    "Failed to download result {:?} from hub: {e}",
+                    result.result_uid
+                ))),
+            }
+        }
+
+        results
+    }
+
+    /// Creates a download request on the hub and returns its UID
+    async fn submit(&self, url: &Url) -> Result<String, String> {
+        let requests = send::<Vec<HubRequest>>(
+            self.request(Method::POST, "v1/download/requests")?
+                .json(&serde_json::json!({ "url": url })),
+        )
+        .await
+        .map_err(|e| format!("Failed to submit {:?} to hub: {e}", url.as_str()))?;
+
+        let request = requests
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("Hub did not create a request for {:?}", url.as_str()))?;
+
+        info!(url = ?url.as_str(), uid = ?request.request_uid, "Submitted to hub");
+
+        Ok(request.request_uid)
+    }
+
+    /// Polls the request until the hub is done with it or the maximum wait time has passed
+    async fn wait_for(&self, request_uid: &str) -> Result<HubRequestInfo, String> {
+        let started = Instant::now();
+        let mut last_status = None;
+
+        loop {
+            let info = send::<HubRequestInfo>(
+                self.request(Method::GET, &format!("v1/download/requests/{request_uid}"))?,
+            )
+            .await
+            .map_err(|e| format!("Failed to get status of hub request {request_uid:?}: {e}"))?;
+
+            if last_status != Some(info.request.status) {
+                debug!(uid = ?request_uid, status = ?info.request.status, "Hub request status");
+                last_status = Some(info.request.status);
+            }
+
+            if matches!(
+                info.request.status,
+                HubItemStatus::Success | HubItemStatus::Failed
+            ) {
+                return Ok(info);
+            }
+
+            if started.elapsed() >= self.max_wait {
+                return Err(format!(
+                    "Timed out waiting for hub request {request_uid:?} after {}s (status: {:?})",
+                    self.max_wait.as_secs(),
+                    info.request.status
+                ));
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    fn request(&self, method: Method, endpoint: &str) -> Result<RequestBuilder, String> {
+        let url = self
+            .base_url
+            .join(endpoint)
+            .map_err(|e| format!("Failed to build hub URL: {e:?}"))?;
+
+        let mut auth = header::HeaderValue::from_str(&format!("Client-Key {}", self.api_key))
+            .map_err(|e| format!("Invalid API key: {e:?}"))?;
+        auth.set_sensitive(true);
+
+        Ok(self
+            .client
+            .request(method, url)
+            .header(header::AUTHORIZATION, auth))
+    }
+}
+
+async fn send<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, String> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request: {e:?}"))?;
+
+    let status = response.status();
+    let response = response
+        .json::<ApiResponse<T>>()
+        .await
+        .map_err(|e| format!("Failed to parse response ({status}): {e:?}"))?;
+
+    match response.body {
+        ApiResponseBody::Success(x) => Ok(x),
+        ApiResponseBody::Error(e) => Err(format!("Hub returned an error ({status}): {e}")),
+        ApiResponseBody::Empty => Err(format!("Hub returned an empty response ({status})")),
+    }
+}
@@ -1,19 +1,40 @@
-use std::{collections::HashSet, fmt::Debug, path::PathBuf, result::Result};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    fmt::{Debug, Display},
+    future::Future,
+    path::{Path, PathBuf},
+    result::Result,
+};
 
 use app_actions::{
     actions::{
-        handlers::{file_rename_to_id::RenameToId, split_scenes::SplitScenes},
-        Action, ActionRequest,
+        handlers::{file_rename_to_id::RenameToId, split_scenes::SplitScenes, ActionEntry},
+        Action, ActionOptions, ActionRequest, ActionResultData, AVAILABLE_ACTIONS,
     },
-    download_file, fix_file,
+    download_file,
+    extractors::{ExtractInfoRequest, PostMetadata},
+    fix_file,
+    fixers::{FixRequest, FixResult},
 };
-use futures::{stream::FuturesUnordered, StreamExt};
+use futures::{stream, StreamExt};
 use tracing::{debug, error, info, warn};
 use tracing_subscriber::{filter::LevelFilter, util::SubscriberInitExt};
+use url::Url;
 
-use crate::config::Config;
+use crate::{
+    archive::DownloadArchive,
+    config::{ActionSpec, Command, Config, OutputFormat},
+    hub::HubClient,
+    report::{Report, Stage},
+};
 
+mod archive;
 mod config;
+mod explain;
+mod hub;
+mod report;
+mod watch;
 
 #[tokio::main]
 #[allow(clippy::too_many_lines)]
@@ -32,7 +53,44 @@ async fn main() {
 
     let cli_config = &config.run;
 
-    for x in &cli_config.entries_group.urls_or_files {
+    let actions = match resolve_actions(&cli_config.actions) {
+        Ok(x) => x,
+        Err(e) => {
+            error!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    let hub = match HubClient::from_config(&config.hub) {
+        Ok(x) => x,
+        Err(e) => {
+            error!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(Command::Watch(watch_config)) = &config.command {
+        if hub.is_some() {
+            error!("Watching a directory is not supported when using a hub");
+            std::process::exit(1);
+        }
+
+        if let Err(e) = watch::run(watch_config, &actions).await {
+            error!("{e}");
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    let batch_entries = print_errors("batch files", get_batch_entries());
+
+    for x in cli_config
+        .entries_group
+        .urls_or_files
+        .iter()
+        .chain(&batch_entries)
+    {
         let mut errs = vec![];
 
         match parse_url(x) {
@@ -56,61 +114,207 @@ async fn main() {
 
     debug!(urls = ?urls, files = ?files, "Parsed urls and files");
 
+    if hub.is_some() && !files.is_empty() {
+        error!("Local files can't be fixed when using a hub, only URLs are supported");
+        std::process::exit(1);
+    }
+
+    if cli_config.explain {
+        let code = explain::run(&urls, &files).await;
+        if code != 0 {
+            std::process::exit(code);
+        }
+
+        return;
+    }
+
     info!("Outputting to {:?}", cli_config.output_directory);
 
+    let mut archive = match &cli_config.download_archive {
+        Some(path) => match DownloadArchive::load(path).await {
+            Ok(x) => Some(x),
+            Err(e) => {
+                error!("Failed to load download archive {path:?}: {e}");
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let (urls, skipped_urls) = urls.into_iter().partition::<Vec<_>, _>(|url| {
+        !archive
+            .as_ref()
+            .is_some_and(|x| x.contains(url, cli_config.redownload_missing))
+    });
+
+    let mut report = Report::default();
+    for url in &urls {
+        report.add_url(url);
+    }
+    for url in &skipped_urls {
+        info!(
+            "Skipping {:?} as it is already in the download archive",
+            url.as_str()
+        );
+        report.add_skipped_url(url);
+    }
+    for file in &files {
+        report.add_file(file);
+    }
+
     info!("Starting download");
-    let downloaded_urls = urls
-        .into_iter()
-        .map(|url| async move {
-            let url_str = url.to_string();
-            download_file(url, &cli_config.output_directory)
-                .await
-                .into_iter()
-                .map(|x| x.map_err(|e| (url_str.clone(), e)))
-                .collect::<Vec<_>>()
-        })
-        .collect::<FuturesUnordered<_>>()
-        .collect::<Vec<_>>()
-        .await
-        .into_iter()
-        .flatten()
-        .collect::<Vec<_>>();
+    let hub = &hub;
+    let downloaded_urls = urls.into_iter().map(|url| async move {
+        let url_str = url.to_string();
+        let results = match hub {
+            Some(hub) => hub.download(&url, &cli_config.output_directory).await,
+            None => {
+                let request = ExtractInfoRequest::from(&url)
+                    .with_extractor_option("render-slideshow", cli_config.render_slideshows);
+
+                download_file(request, &cli_config.output_directory).await
+            }
+        };
+
+        results
+            .into_iter()
+            .map(|x| {
+                x.map(|x| (url.clone(), x))
+                    .map_err(|e| (url_str.clone(), e))
+            })
+            .collect::<Vec<_>>()
+    });
+    let downloaded_urls = run_bounded(downloaded_urls).await;
     debug!(urls = ?downloaded_urls, "Downloaded urls");
 
     let (downloaded, failed_downloaded) = split_vec_err(downloaded_urls);
+    for (url, x) in &downloaded {
+        report.downloaded(url, x);
+    }
+    for (url, e) in &failed_downloaded {
+        report.download_failed(url, e);
+    }
+    exit_if_failing_fast("download", &failed_downloaded, &mut report);
     info!(
-        "Download completed: downloaded {} files, failed to download {} files",
+        "Download completed: downloaded {} files, skipped {} archived urls, failed to download {} \
+         files",
         downloaded.len(),
+        skipped_urls.len(),
         failed_downloaded.len()
     );
 
+    let post_metadata = downloaded
+        .iter()
+        .filter_map(|(_, x)| Some((x.path.clone(), x.request.post_metadata.clone()?)))
+        .collect::<HashMap<_, _>>();
+
+    let download_sources = downloaded
+        .iter()
+        .map(|(url, x)| (x.path.clone(), url.clone()))
+        .collect::<HashMap<_, _>>();
+
     let to_fix = downloaded
         .into_iter()
-        .map(|x| x.path)
+        .map(|(_, x)| x.path)
         .chain(files.clone())
         .collect::<Vec<_>>();
 
     debug!(files = ?to_fix, "Files to fix");
     info!("Starting fixing of {} files", to_fix.len());
-    let fixed_files = to_fix
-        .into_iter()
-        .map(|x| async move {
-            fix_file(&x)
-                .await
-                .map(|n| (x.clone(), n))
-                .map_err(|e| (x, e))
-        })
-        .collect::<FuturesUnordered<_>>()
-        .collect::<Vec<_>>()
-        .await;
+    let fixed_files = to_fix.into_iter().map(|x| async move {
+        // The hub already fixed the files it returned
+        let result = if hub.is_some() {
+            Ok(FixResult::new(FixRequest::new(&x), x.clone()))
+        } else {
+            fix_file(&x).await
+        };
+
+        vec![result.map(|n| (x.clone(), n)).map_err(|e| (x, e))]
+    });
+    let fixed_files = run_bounded(fixed_files).await;
 
     let (fixed, failed_fixed) = split_vec_err(fixed_files);
+    for (old, new) in &fixed {
+        report.fixed(old, new);
+    }
+    for (path, e) in &failed_fixed {
+        report.path_failed(path, Stage::Fix, None, e);
+    }
+    exit_if_failing_fast("fix", &failed_fixed, &mut report);
     info!(
         "Fixing completed: fixed {} files, failed to fix {} files",
         fixed.len(),
         failed_fixed.len()
     );
 
+    if let Some(archive) = &mut archive {
+        // URLs with failed files are left out so they get retried on the next run
+        let failed_urls = failed_downloaded
+            .iter()
+            .map(|(url, _)| url.clone())
+            .chain(
+                failed_fixed
+                    .iter()
+                    .filter_map(|(path, _)| download_sources.get(path))
+                    .map(ToString::to_string),
+            )
+            .collect::<HashSet<_>>();
+
+        let mut url_files = HashMap::<&Url, Vec<PathBuf>>::new();
+        for (old, new) in &fixed {
+            if let Some(url) = download_sources.get(old) {
+                url_files
+                    .entry(url)
+                    .or_default()
+                    .push(new.file_path.clone());
+            }
+        }
+
+        for (url, paths) in url_files {
+            if failed_urls.contains(url.as_str()) {
+                debug!(
+                    url = ?url.as_str(),
+                    "Not adding to the download archive as some of its files failed"
+                );
+                continue;
+            }
+
+            if let Err(e) = archive.set(url, &paths).await {
+                error!(
+                    "Failed to add {:?} to the download archive: {e}",
+                    url.as_str()
+                );
+                for path in &paths {
+                    report.path_failed(path, Stage::Archive, None, &e);
+                }
+            }
+        }
+    }
+
+    if cli_config.write_metadata {
+        let fixed_paths = fixed
+            .iter()
+            .map(|(old, new)| (old, new.file_path.as_path()))
+            .collect::<HashMap<_, _>>();
+
+        for (path, metadata) in &post_metadata {
+            let path = fixed_paths.get(path).copied().unwrap_or(path);
+
+            match write_metadata_sidecar(path, metadata).await {
+                Ok(sidecar_path) => report.action_done(
+                    path,
+                    "write-metadata",
+                    ActionResultData::Paths(vec![sidecar_path]),
+                ),
+                Err(e) => {
+                    error!("Failed to write metadata for {path:?}: {e}");
+                    report.path_failed(path, Stage::Action, Some("write-metadata"), e);
+                }
+            }
+        }
+    }
+
+    let mut renamed = HashMap::new();
     if cli_config.and_rename {
         let files_set = {
             let mut new = HashSet::new();
@@ -128,13 +332,66 @@ async fn main() {
                     }
                 };
 
-                if let Err(e) = RenameToId.run(&req).await {
-                    error!("Failed to rename {new:?}: {e:?}");
+                match RenameToId.run(&req).await {
+                    Ok(x) => {
+                        if let ActionResultData::Paths(paths) = &x.data {
+                            if let Some(path) = paths.first() {
+                                report.renamed(&new.file_path, path);
+                                renamed.insert(new.file_path.clone(), path.clone());
+                            }
+                        }
+
+                        report.action_done(&new.file_path, RenameToId.name(), x.data);
+                    }
+                    Err(e) => {
+                        error!("Failed to rename {new:?}: {e:?}");
+                        report.path_failed(
+                            &new.file_path,
+                            Stage::Action,
+                            Some(RenameToId.name()),
+                            e,
+                        );
+                    }
                 }
             }
         }
     }
 
+    let to_run_actions_on = fixed
+        .iter()
+        .map(|(_, new)| {
+            renamed
+                .get(&new.file_path)
+                .cloned()
+                .unwrap_or_else(|| new.file_path.clone())
+        })
+        .collect::<Vec<_>>();
+    info!(
+        "Running {} actions on {} files",
+        actions.len(),
+        to_run_actions_on.len()
+    );
+    let action_results = to_run_actions_on
+        .into_iter()
+        .filter(|_| !actions.is_empty())
+        .map(|path| run_actions(&actions, path));
+    let action_results = run_bounded(action_results).await;
+
+    let (actions_done, failed_actions) = split_vec_err(action_results);
+    for ((name, path), data) in actions_done {
+        if let ActionResultData::Text(text) = &data {
+            if cli_config.output_format == OutputFormat::Text {
+                println!("{text}");
+            }
+        }
+
+        report.action_done(&path, name, data);
+    }
+    for ((name, path), e) in &failed_actions {
+        report.path_failed(path, Stage::Action, Some(name), e);
+    }
+    exit_if_failing_fast("run action", &failed_actions, &mut report);
+
     let split_files = get_explicit_split_files()
         .into_iter()
         .flatten()
@@ -143,14 +400,28 @@ async fn main() {
     let mut failed_split = vec![];
     for f in split_files {
         let req = ActionRequest::new(f.clone(), cli_config.output_directory.clone());
-
-        if let Err(e) = SplitScenes.run(&req).await {
-            error!("Failed to split {f:?}: {e}");
-            failed_split.push((f.clone(), e));
+        report.add_split_file(&f);
+
+        match SplitScenes.run(&req).await {
+            Ok(x) => report.action_done(&f, SplitScenes.name(), x.data),
+            Err(e) => {
+                error!("Failed to split {f:?}: {e}");
+                report.path_failed(&f, Stage::Action, Some(SplitScenes.name()), &e);
+                failed_split.push((f.clone(), e));
+
+                if cli_config.fail_fast {
+                    report.finish();
+                    std::process::exit(1);
+                }
+            }
         }
     }
 
-    if !failed_downloaded.is_empty() || !failed_fixed.is_empty() || !failed_split.is_empty() {
+    if !failed_downloaded.is_empty()
+        || !failed_fixed.is_empty()
+        || !failed_actions.is_empty()
+        || !failed_split.is_empty()
+    {
         for (x, e) in failed_downloaded {
             error!("Failed to download {x:?}: {e}");
         }
@@ -159,12 +430,132 @@ async fn main() {
             error!("Failed to fix {x:?}: {e}");
         }
 
+        for (x, e) in failed_actions {
+            error!("Failed to run action {x:?}: {e}");
+        }
+
         for (x, e) in failed_split {
             error!("Failed to split {x:?}: {e}");
         }
+    }
 
-        std::process::exit(1);
+    report.finish();
+}
+
+async fn write_metadata_sidecar(
+    file_path: &Path,
+    metadata: &PostMetadata,
+) -> Result<PathBuf, String> {
+    let sidecar_path = {
+        let mut path = OsString::from(file_path.as_os_str());
+        path.push(".json");
+        PathBuf::from(path)
+    };
+
+    let contents = serde_json::to_string_pretty(metadata)
+        .map_err(|e| format!("Failed to serialize metadata: {e:?}"))?;
+
+    tokio::fs::write(&sidecar_path, contents)
+        .await
+        .map_err(|e| format!("Failed to write {sidecar_path:?}: {e:?}"))?;
+
+    debug!(?sidecar_path, "Wrote metadata sidecar");
+
+    Ok(sidecar_path)
+}
+
+/// Finds the actions to run on every fixed file
+fn resolve_actions(specs: &[ActionSpec]) -> Result<Vec<(ActionEntry, ActionOptions)>, String> {
+    specs
+        .iter()
+        .map(|spec| {
+            AVAILABLE_ACTIONS
+                .iter()
+                .find(|x| x.name().eq_ignore_ascii_case(&spec.name))
+                .map(|x| (x.clone(), spec.options.clone()))
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown or unavailable action {:?}. Available actions: {}",
+                        spec.name,
+                        AVAILABLE_ACTIONS
+                            .iter()
+                            .map(|x| x.name())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Runs the actions one after another on the file, skipping the ones that can't handle it
+async fn run_actions(
+    actions: &[(ActionEntry, ActionOptions)],
+    path: PathBuf,
+) -> Vec<Result<((&'static str, PathBuf), ActionResultData), ((&'static str, PathBuf), String)>> {
+    let mut results = vec![];
+
+    for (action, options) in actions {
+        let req = ActionRequest::new(path.clone(), Config::run().output_directory.clone())
+            .with_options(options.clone());
+
+        if !action.can_run_for(&req).await {
+            debug!(action = action.name(), ?path, "Action can't run for file");
+            continue;
+        }
+
+        let key = (action.name(), path.clone());
+        results.push(match action.run(&req).await {
+            Ok(x) => Ok((key, x.data)),
+            Err(e) => Err((key, e.to_string())),
+        });
     }
+
+    results
+}
+
+/// Runs the tasks with at most `concurrency` of them at the same time.
+///
+/// When failing fast, no new tasks are started after the first failure.
+/// The tasks that are still running get dropped, which kills the programs they started.
+async fn run_bounded<I, Fut, T, E>(tasks: I) -> Vec<Result<T, E>>
+where
+    I: IntoIterator<Item = Fut>,
+    Fut: Future<Output = Vec<Result<T, E>>>,
+{
+    let cli_config = Config::run();
+    let mut tasks = stream::iter(tasks).buffer_unordered(cli_config.concurrency);
+    let mut results = vec![];
+
+    while let Some(task_results) = tasks.next().await {
+        let failed = task_results.iter().any(Result::is_err);
+        results.extend(task_results);
+
+        if failed && cli_config.fail_fast {
+            warn!("Stopping after the first failure");
+            break;
+        }
+    }
+
+    results
+}
+
+/// Finishes the report and exits if failing fast and any of the tasks failed
+fn exit_if_failing_fast<T: Debug, E: Display>(
+    action: &str,
+    failed: &[(T, E)],
+    report: &mut Report,
+) {
+    if !Config::run().fail_fast || failed.is_empty() {
+        return;
+    }
+
+    for (x, e) in failed {
+        error!("Failed to {action} {x:?}: {e}");
+    }
+
+    report.finish();
+    std::process::exit(1);
 }
 
 fn split_vec_err<T: Debug, E: Debug>(v: Vec<Result<T, E>>) -> (Vec<T>, Vec<E>) {
@@ -210,6 +601,33 @@ fn get_explicit_urls() -> Vec<Result<url::Url, String>> {
         .collect::<Vec<_>>()
 }
 
+/// Reads the entries of the batch files, skipping empty lines and comments
+fn get_batch_entries() -> Vec<Result<String, String>> {
+    Config::run()
+        .entries_group
+        .batch_files
+        .iter()
+        .map(|f| {
+            let contents = if f == "-" {
+                std::io::read_to_string(std::io::stdin())
+            } else {
+                std::fs::read_to_string(f)
+            };
+
+            contents.map_err(|e| format!("Failed to read batch file {f:?}: {e}"))
+        })
+        .flat_map(|contents| match contents {
+            Ok(contents) => contents
+                .lines()
+                .map(str::trim)
+                .filter(|x| !x.is_empty() && !x.starts_with(['#', ';']))
+                .map(|x| Ok(x.to_string()))
+                .collect::<Vec<_>>(),
+            Err(e) => vec![Err(e)],
+        })
+        .collect()
+}
+
 fn parse_url(u: &str) -> Result<url::Url, String> {
     url::Url::parse(u).map_err(|x| x.to_string())
 }
@@ -257,6 +675,7 @@ fn parse_file<T: Into<PathBuf>>(f: T) -> Result<PathBuf, String> {
 fn init_log() {
     tracing_subscriber::fmt()
         .with_ansi(true)
+        .with_writer(std::io::stderr)
         .with_env_filter(
             tracing_subscriber::filter::Builder::default()
                 .with_default_directive(LevelFilter::INFO.into())
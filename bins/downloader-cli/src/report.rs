@@ -0,0 +1,290 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use app_actions::{actions::ActionResultData, downloaders::DownloadResult, fixers::FixResult};
+use serde::Serialize;
+use tracing::error;
+use url::Url;
+
+use crate::config::{Config, OutputFormat};
+
+/// Results of the run, grouped by the input (URL or file) they came from.
+#[derive(Debug, Default)]
+pub struct Report {
+    records: Vec<InputRecord>,
+    /// Record index for every input URL
+    urls: HashMap<String, usize>,
+    /// Record and file index for every path that is still being processed
+    paths: HashMap<PathBuf, (usize, Option<usize>)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InputRecord {
+    /// The URL or file path as it was passed in
+    pub source: String,
+    pub kind: InputKind,
+    pub status: InputStatus,
+    pub files: Vec<FileRecord>,
+    pub actions: Vec<ActionRecord>,
+    pub errors: Vec<ErrorRecord>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputKind {
+    Url,
+    File,
+    SplitFile,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputStatus {
+    Ok,
+    /// Some of the steps failed but at least one file was produced
+    Partial,
+    Failed,
+    /// Already in the download archive
+    Skipped,
+    /// The run stopped before getting to the input (eg. with `--fail-fast`)
+    NotRun,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct FileRecord {
+    pub extractor: Option<String>,
+    pub downloader: Option<String>,
+    pub downloaded_path: Option<PathBuf>,
+    pub fixed_path: Option<PathBuf>,
+    pub fixers: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActionRecord {
+    pub action: String,
+    pub path: PathBuf,
+    pub output: ActionResultData,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorRecord {
+    pub stage: Stage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Download,
+    Fix,
+    Archive,
+    Action,
+}
+
+impl Report {
+    pub fn add_url(&mut self, url: &Url) {
+        let idx = self.push(url.to_string(), InputKind::Url);
+        self.urls.insert(url.to_string(), idx);
+    }
+
+    pub fn add_skipped_url(&mut self, url: &Url) {
+        let idx = self.push(url.to_string(), InputKind::Url);
+        self.records[idx].status = InputStatus::Skipped;
+    }
+
+    pub fn add_file(&mut self, path: &Path) {
+        let idx = self.push(path.display().to_string(), InputKind::File);
+        self.records[idx].files.push(FileRecord::default());
+        self.paths.insert(path.to_path_buf(), (idx, Some(0)));
+    }
+
+    pub fn add_split_file(&mut self, path: &Path) {
+        let idx = self.push(path.display().to_string(), InputKind::SplitFile);
+        self.paths.insert(path.to_path_buf(), (idx, None));
+    }
+
+    pub fn downloaded(&mut self, url: &Url, result: &DownloadResult) {
+        let Some(&idx) = self.urls.get(url.as_str()) else {
+            return;
+        };
+
+        let files = &mut self.records[idx].files;
+        files.push(FileRecord {
+            extractor: result.request.extractor.clone(),
+            downloader: result.downloader.clone(),
+            downloaded_path: Some(result.path.clone()),
+            ..Default::default()
+        });
+        self.paths
+            .insert(result.path.clone(), (idx, Some(files.len() - 1)));
+    }
+
+    pub fn download_failed<E: Display>(&mut self, url: &str, error: E) {
+        let Some(&idx) = self.urls.get(url) else {
+            return;
+        };
+
+        self.records[idx].errors.push(ErrorRecord {
+            stage: Stage::Download,
+            action: None,
+            path: None,
+            message: error.to_string(),
+        });
+    }
+
+    pub fn fixed(&mut self, old_path: &Path, result: &FixResult) {
+        let Some(&(idx, file_idx)) = self.paths.get(old_path) else {
+            return;
+        };
+
+        if let Some(file) = file_idx.and_then(|x| self.records[idx].files.get_mut(x)) {
+            file.fixed_path = Some(result.file_path.clone());
+            file.fixers.clone_from(&result.applied_fixers);
+        }
+
+        self.paths.insert(result.file_path.clone(), (idx, file_idx));
+    }
+
+    /// Makes results for the renamed file end up in the same record as the old one
+    pub fn renamed(&mut self, old_path: &Path, new_path: &Path) {
+        if let Some(&entry) = self.paths.get(old_path) {
+            self.paths.insert(new_path.to_path_buf(), entry);
+        }
+    }
+
+    pub fn action_done(&mut self, path: &Path, action: &str, output: ActionResultData) {
+        let Some(&(idx, _)) = self.paths.get(path) else {
+            return;
+        };
+
+        self.records[idx].actions.push(ActionRecord {
+            action: action.to_string(),
+            path: path.to_path_buf(),
+            output,
+        });
+    }
+
+    /// Records an error for the input the path came from.
+    ///
+    /// `action` should be set for errors of the [`Stage::Action`] stage.
+    pub fn path_failed<E: Display>(
+        &mut self,
+        path: &Path,
+        stage: Stage,
+        action: Option<&str>,
+        error: E,
+    ) {
+        let Some(&(idx, _)) = self.paths.get(path) else {
+            return;
+        };
+
+        self.records[idx].errors.push(ErrorRecord {
+            stage,
+            action: action.map(ToString::to_string),
+            path: Some(path.to_path_buf()),
+            message: error.to_string(),
+        });
+    }
+
+    /// Prints the records if the JSON output format is used
+    /// and exits with a code based on how many of the inputs failed.
+    ///
+    /// Only exits the process on failure.
+    pub fn finish(&mut self) {
+        for record in &mut self.records {
+            record.status = record.resolve_status();
+        }
+
+        if Config::run().output_format == OutputFormat::Json {
+            for record in &self.records {
+                match serde_json::to_string(record) {
+                    Ok(x) => println!("{x}"),
+                    Err(e) => error!("Failed to serialize record for {:?}: {e:?}", record.source),
+                }
+            }
+        }
+
+        let code = self.exit_code();
+        if code != 0 {
+            std::process::exit(code);
+        }
+    }
+
+    /// `0` if all inputs succeeded, `1` if none of them did and `2` otherwise.
+    ///
+    /// Inputs that were not run don't count as succeeded.
+    fn exit_code(&self) -> i32 {
+        let processed = self
+            .records
+            .iter()
+            .filter(|x| x.status != InputStatus::Skipped)
+            .count();
+        let succeeded = self
+            .records
+            .iter()
+            .filter(|x| x.status == InputStatus::Ok)
+            .count();
+        let partial = self
+            .records
+            .iter()
+            .filter(|x| x.status == InputStatus::Partial)
+            .count();
+
+        if succeeded == processed {
+            0
+        } else if succeeded == 0 && partial == 0 {
+            1
+        } else {
+            2
+        }
+    }
+
+    fn push(&mut self, source: String, kind: InputKind) -> usize {
+        self.records.push(InputRecord {
+            source,
+            kind,
+            status: InputStatus::Ok,
+            files: vec![],
+            actions: vec![],
+            errors: vec![],
+        });
+
+        self.records.len() - 1
+    }
+}
+
+impl InputRecord {
+    fn resolve_status(&self) -> InputStatus {
+        if self.status == InputStatus::Skipped {
+            return InputStatus::Skipped;
+        }
+
+        let produced_anything = !self.actions.is_empty()
+            || self
+                .files
+                .iter()
+                .any(|x| x.downloaded_path.is_some() || x.fixed_path.is_some());
+
+        if self.errors.is_empty() {
+            // URLs and split files always produce something or an error once they are processed
+            return if produced_anything || self.kind == InputKind::File {
+                InputStatus::Ok
+            } else {
+                InputStatus::NotRun
+            };
+        }
+
+        if produced_anything {
+            InputStatus::Partial
+        } else {
+            InputStatus::Failed
+        }
+    }
+}
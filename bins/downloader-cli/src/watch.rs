@@ -0,0 +1,383 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use app_actions::{
+    actions::{handlers::ActionEntry, ActionOptions, ActionResultData},
+    download_file, fix_file,
+};
+use notify::{
+    event::{AccessKind, AccessMode, ModifyKind},
+    EventKind, RecursiveMode, Watcher,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt, sync::mpsc};
+use tracing::{debug, error, info, warn};
+use url::Url;
+
+use crate::{
+    archive::file_hash,
+    config::{Config, WatchConfig},
+    run_actions,
+};
+
+const DEFAULT_STATE_FILE_NAME: &str = ".downloader-cli-watch.jsonl";
+/// Files that contain links to download instead of media to fix
+const LINK_FILE_EXTENSIONS: &[&str] = &["url", "txt"];
+/// Extensions of files that other programs are still writing to
+const TEMPORARY_FILE_EXTENSIONS: &[&str] =
+    &["part", "partial", "tmp", "crdownload", "download", "ytdl"];
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Hashes of the files that were already processed, stored as one JSON entry per line.
+#[derive(Debug)]
+struct ProcessedFiles {
+    path: PathBuf,
+    hashes: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ProcessedEntry {
+    hash: String,
+    path: PathBuf,
+}
+
+impl ProcessedFiles {
+    async fn load(path: &Path) -> Result<Self, String> {
+        let mut processed = Self {
+            path: path.to_path_buf(),
+            hashes: HashSet::new(),
+        };
+
+        let contents = match tokio::fs::read_to_string(path).await {
+            Ok(x) => x,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(processed),
+            Err(e) => return Err(format!("Failed to read watch state file: {e:?}")),
+        };
+
+        for (i, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str::<ProcessedEntry>(line) {
+                Ok(entry) => {
+                    processed.hashes.insert(entry.hash);
+                }
+                Err(e) => warn!("Skipping invalid watch state line {}: {e}", i + 1),
+            }
+        }
+
+        debug!(count = processed.hashes.len(), "Loaded watch state");
+
+        Ok(processed)
+    }
+
+    fn contains(&self, hash: &str) -> bool {
+        self.hashes.contains(hash)
+    }
+
+    async fn add(&mut self, hash: String, file_path: &Path) -> Result<(), String> {
+        if self.contains(&hash) {
+            return Ok(());
+        }
+
+        let entry = ProcessedEntry {
+            hash,
+            path: file_path.to_path_buf(),
+        };
+
+        let mut line = serde_json::to_string(&entry)
+            .map_err(|e| format!("Failed to serialize watch state entry: {e:?}"))?;
+        line.push('\n');
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|e| format!("Failed to open watch state file: {e:?}"))?
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write to watch state file: {e:?}"))?;
+
+        self.hashes.insert(entry.hash);
+
+        Ok(())
+    }
+
+    async fn add_file(&mut self, file_path: &Path) -> Result<(), String> {
+        let hash = file_hash(file_path).await?;
+
+        self.add(hash, file_path).await
+    }
+}
+
+/// Watches the directory and processes files once they stop changing.
+///
+/// Runs until the process is stopped.
+pub async fn run(
+    watch_config: &WatchConfig,
+    actions: &[(ActionEntry, ActionOptions)],
+) -> Result<(), String> {
+    let directory = &watch_config.directory;
+    let state_file = watch_config
+        .state_file
+        .clone()
+        .unwrap_or_else(|| directory.join(DEFAULT_STATE_FILE_NAME));
+    let mut processed = ProcessedFiles::load(&state_file).await?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher =
+        notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+            Ok(event) if is_relevant_event(event.kind) => {
+                for path in event.paths {
+                    let _ = tx.send(path);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to watch for file changes: {e:?}"),
+        })
+        .map_err(|e| format!("Failed to create watcher: {e:?}"))?;
+
+    let mode = if watch_config.recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    watcher
+        .watch(directory, mode)
+        .map_err(|e| format!("Failed to watch {directory:?}: {e:?}"))?;
+
+    info!("Watching {directory:?} for new files");
+
+    let settle_time = Duration::from_secs(watch_config.settle_seconds);
+    // Files that were added while nothing was watching the directory
+    let mut pending = existing_files(directory, watch_config.recursive)
+        .into_iter()
+        .map(|x| (x, Instant::now()))
+        .collect::<HashMap<_, _>>();
+
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            path = rx.recv() => {
+                let Some(path) = path else {
+                    break;
+                };
+
+                pending.insert(path, Instant::now());
+            }
+
+            _ = interval.tick() => {
+                let ready = pending
+                    .iter()
+                    .filter(|(_, changed_at)| changed_at.elapsed() >= settle_time)
+                    .map(|(path, _)| path.clone())
+                    .collect::<Vec<_>>();
+
+                for path in ready {
+                    pending.remove(&path);
+
+                    if path != state_file {
+                        process_path(&path, actions, &mut processed).await;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+const fn is_relevant_event(kind: EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_)
+            | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_) | ModifyKind::Any)
+            | EventKind::Access(AccessKind::Close(AccessMode::Write))
+    )
+}
+
+async fn process_path(
+    path: &Path,
+    actions: &[(ActionEntry, ActionOptions)],
+    processed: &mut ProcessedFiles,
+) {
+    if !should_process(path) {
+        return;
+    }
+
+    let hash = match file_hash(path).await {
+        Ok(x) => x,
+        Err(e) => {
+            warn!("Failed to hash {path:?}: {e}");
+            return;
+        }
+    };
+
+    if processed.contains(&hash) {
+        debug!(?path, "Skipping already processed file");
+        return;
+    }
+
+    info!("Processing {path:?}");
+    let output_paths = if is_link_file(path) {
+        download_links(path, actions).await
+    } else {
+        fix_and_run_actions(path, actions).await
+    };
+
+    let output_paths = match output_paths {
+        Ok(x) => x,
+        Err(e) => {
+            error!("Failed to process {path:?}: {e}");
+            return;
+        }
+    };
+
+    if let Err(e) = processed.add(hash, path).await {
+        error!("Failed to record {path:?} as processed: {e}");
+    }
+
+    // The results may end up in the watched directory, so they shouldn't be processed again
+    for output_path in output_paths {
+        if let Err(e) = processed.add_file(&output_path).await {
+            error!("Failed to record {output_path:?} as processed: {e}");
+        }
+    }
+
+    info!("Processed {path:?}");
+}
+
+/// Downloads the links in the file and fixes the downloaded files
+async fn download_links(
+    path: &Path,
+    actions: &[(ActionEntry, ActionOptions)],
+) -> Result<Vec<PathBuf>, String> {
+    let contents = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("Failed to read links: {e:?}"))?;
+
+    let links = extract_links(&contents);
+    if links.is_empty() {
+        return Err("No links found in file".to_string());
+    }
+
+    debug!(?links, "Downloading links");
+
+    let mut output_paths = vec![];
+    let mut errors = vec![];
+    for link in links {
+        for result in download_file(link.clone(), &Config::run().output_directory).await {
+            let downloaded = match result {
+                Ok(x) => x,
+                Err(e) => {
+                    errors.push(format!("Failed to download {:?}: {e}", link.as_str()));
+                    continue;
+                }
+            };
+
+            match fix_and_run_actions(&downloaded.path, actions).await {
+                Ok(x) => output_paths.extend(x),
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+
+    if output_paths.is_empty() {
+        return Err(errors.join(", "));
+    }
+
+    for e in errors {
+        error!("{e}");
+    }
+
+    Ok(output_paths)
+}
+
+/// Fixes the file and runs the actions on it.
+///
+/// Returns the fixed file and any files the actions produced.
+async fn fix_and_run_actions(
+    path: &Path,
+    actions: &[(ActionEntry, ActionOptions)],
+) -> Result<Vec<PathBuf>, String> {
+    let fixed = fix_file(path)
+        .await
+        .map_err(|e| format!("Failed to fix {path:?}: {e}"))?;
+
+    let mut output_paths = vec![fixed.file_path.clone()];
+    for result in run_actions(actions, fixed.file_path).await {
+        match result {
+            Ok((_, ActionResultData::Text(text))) => println!("{text}"),
+            Ok((_, ActionResultData::Paths(paths))) => output_paths.extend(paths),
+            Err(((name, path), e)) => error!("Failed to run {name} on {path:?}: {e}"),
+        }
+    }
+
+    Ok(output_paths)
+}
+
+fn should_process(path: &Path) -> bool {
+    if !path.is_file() {
+        return false;
+    }
+
+    let is_hidden = path
+        .file_name()
+        .and_then(|x| x.to_str())
+        .is_none_or(|x| x.starts_with('.'));
+
+    !is_hidden && !has_extension(path, TEMPORARY_FILE_EXTENSIONS)
+}
+
+fn is_link_file(path: &Path) -> bool {
+    has_extension(path, LINK_FILE_EXTENSIONS)
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|x| x.to_str())
+        .is_some_and(|x| extensions.iter().any(|e| e.eq_ignore_ascii_case(x)))
+}
+
+/// Finds the links in plain text or internet shortcut (`URL=...`) files
+fn extract_links(contents: &str) -> Vec<Url> {
+    let mut links = contents
+        .lines()
+        .map(str::trim)
+        .map(|line| line.strip_prefix("URL=").unwrap_or(line))
+        .flat_map(str::split_whitespace)
+        .filter_map(|x| Url::parse(x).ok())
+        .filter(|x| matches!(x.scheme(), "http" | "https"))
+        .collect::<Vec<_>>();
+
+    links.dedup();
+
+    links
+}
+
+fn existing_files(directory: &Path, recursive: bool) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return vec![];
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .map(|x| x.path())
+        .flat_map(|path| {
+            if path.is_dir() {
+                if recursive {
+                    existing_files(&path, recursive)
+                } else {
+                    vec![]
+                }
+            } else {
+                vec![path]
+            }
+        })
+        .collect()
+}
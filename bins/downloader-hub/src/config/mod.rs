@@ -22,6 +22,14 @@ pub struct Config {
     #[validate(nested)]
     pub endpoint: common::EndpointConfig,
 
+    #[clap(flatten)]
+    #[validate(nested)]
+    pub credentials: common::CredentialsConfig,
+
+    #[clap(flatten)]
+    #[validate(nested)]
+    pub proxy: common::ProxyConfig,
+
     #[clap(flatten)]
     #[validate(nested)]
     pub task: common::TaskConfig,
@@ -41,7 +49,12 @@ impl Config {
 
         {
             let parsed = parsed.clone();
-            app_actions::config::init(parsed.endpoint, parsed.dependency_paths)?;
+            app_actions::config::init(
+                parsed.endpoint,
+                parsed.dependency_paths,
+                parsed.credentials,
+                parsed.proxy,
+            )?;
         }
 
         {
@@ -1,9 +1,19 @@
 use std::result::Result;
 
-use app_actions::download_file;
+use app_actions::{
+    download_file,
+    downloaders::{
+        handlers::yt_dlp::{QualityPreset, YtDlp},
+        Downloader,
+    },
+    extractors::ExtractInfoRequest,
+};
 use app_entities::{
     download_request,
-    entity_meta::{common::path::AppPath, download_result::DownloadResultStatus},
+    entity_meta::{
+        common::path::AppPath,
+        download_result::{DownloadResultSource, DownloadResultStatus},
+    },
 };
 use app_helpers::ip::url_resolves_to_valid_ip;
 use sea_orm::{prelude::*, TransactionTrait};
@@ -58,6 +68,7 @@ pub(super) async fn handle_download_request(uid: &str) -> Result<(), HandlerErro
     }
 }
 
+#[allow(clippy::too_many_lines)]
 #[tracing::instrument]
 async fn download(uid: &str) -> Result<(download_request::Model, Vec<AppPath>), HandlerError> {
     info!("Got download request");
@@ -83,12 +94,29 @@ async fn download(uid: &str) -> Result<(download_request::Model, Vec<AppPath>),
 
     debug!(dir = ?download_dir, url = ?download_url.as_str(), "Staring download");
 
-    let results = download_file(&download_url, &download_dir).await;
+    let extract_request = ExtractInfoRequest::new(download_url)
+        .with_method(request_meta.request.method.clone())
+        .with_headers(request_meta.request.headers.clone())
+        .with_extractor_options(request_meta.extractor_options.clone())
+        .with_downloader_options(request_meta.downloader_options.clone())
+        .with_downloader_defaults(
+            YtDlp.name(),
+            YtDlp::options().with_quality(Some(QualityPreset::Best)),
+        );
+
+    let results = download_file(extract_request, &download_dir).await;
 
     debug!(?results, "Download completed successfully");
 
+    let post_metadata = results
+        .iter()
+        .filter_map(|x| x.as_ref().ok())
+        .find_map(|x| x.request.post_metadata.as_ref())
+        .and_then(|x| serde_json::to_value(x).ok());
+
     let results = app_helpers::futures::retry_fn(5, || {
         let results = results.clone();
+        let post_metadata = post_metadata.clone();
 
         db.transaction_with_config::<_, _, DbErr>(
             |txn| {
@@ -101,6 +129,8 @@ async fn download(uid: &str) -> Result<(download_request::Model, Vec<AppPath>),
                     )
                     .await?;
 
+                    DownloadRequestService::update_post_metadata(txn, &uid, post_metadata).await?;
+
                     DownloadResultService::create_many(
                         txn,
                         results.iter().map(|x| match x {
@@ -113,12 +143,22 @@ async fn download(uid: &str) -> Result<(download_request::Model, Vec<AppPath>),
                                 },
                                 path: Some(x.path.clone()),
                                 meta: None,
+                                media_info: x
+                                    .media_info
+                                    .as_ref()
+                                    .and_then(|x| serde_json::to_value(x).ok()),
+                                source: Some(DownloadResultSource {
+                                    extractor: x.request.extractor.clone(),
+                                    downloader: x.downloader.clone(),
+                                }),
                             },
                             Err(e) => CreateDownloadResultPayload {
                                 request_id: request.id,
                                 status: DownloadResultStatus::Failed(e.clone()),
                                 path: None,
                                 meta: None,
+                                media_info: None,
+                                source: None,
                             },
                         }),
                     )
@@ -1,5 +1,6 @@
 use std::sync::OnceLock;
 
+use app_actions::canonical_url::canonicalize_url;
 use app_entities::{
     download_request, download_result,
     entity_meta::download_request::{
@@ -17,6 +18,7 @@ use futures::StreamExt;
 use sea_orm::prelude::*;
 use serde::{Deserialize, Serialize};
 use tower_http::request_id::RequestId;
+use url::Url;
 
 use crate::{
     db::AppDb,
@@ -169,6 +171,9 @@ async fn create_request(
         .into_iter()
         .map(|url| CreateDownloadRequestPayload {
             client_id: user.id,
+            canonical_url: Url::parse(&url.url)
+                .ok()
+                .map(|x| canonicalize_url(&x).to_string()),
             url: url.url,
             meta: url.meta,
             app_meta: app_meta.clone(),
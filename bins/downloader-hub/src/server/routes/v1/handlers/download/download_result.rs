@@ -11,7 +11,7 @@ use axum::{
     routing::get,
     Router,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tracing::{error, trace};
 
 use crate::{
@@ -42,11 +42,18 @@ pub(super) fn router() -> AppRouter {
 struct GetResultInfoQuery {
     share_for_seconds: Option<i64>,
 }
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetResultInfoResponse {
+    #[serde(flatten)]
+    result: WithDownloadUrl<download_result::Model>,
+    media_info: Option<serde_json::Value>,
+}
 async fn get_result_info(
     Path(result_uid): Path<String>,
     OriginalUri(uri): OriginalUri,
     Query(query): Query<GetResultInfoQuery>,
-) -> V1Result<WithDownloadUrl<download_result::Model>> {
+) -> V1Result<GetResultInfoResponse> {
     let result = DownloadResultService::find_by_uid(&AppDb::db(), &result_uid)
         .await?
         .ok_or_else(V1Response::not_found)?;
@@ -58,12 +65,15 @@ async fn get_result_info(
             .map_or_else(|| chrono::Duration::hours(6), chrono::Duration::seconds),
     );
 
-    Ok(V1Response::Success(WithDownloadUrl {
-        inner: result,
-        download_url: Some(
-            sig.to_absulute_url_from_path(format!("{}/download", uri.path()))
-                .to_string(),
-        ),
+    Ok(V1Response::Success(GetResultInfoResponse {
+        media_info: result.media_info().cloned(),
+        result: WithDownloadUrl {
+            inner: result,
+            download_url: Some(
+                sig.to_absulute_url_from_path(format!("{}/download", uri.path()))
+                    .to_string(),
+            ),
+        },
     }))
 }
 
@@ -0,0 +1,64 @@
+use app_actions::{
+    downloaders::{
+        handlers::yt_dlp::{QualityPreset, YtDlp},
+        Downloader,
+    },
+    explain::{explain_url, DownloadPlan},
+    extractors::ExtractInfoRequest,
+};
+use app_entities::entity_meta::download_request::DownloadRequestMeta;
+use app_helpers::ip::url_resolves_to_valid_ip;
+use axum::{http::StatusCode, middleware, routing::post, Extension, Json, Router};
+use axum_extra::extract::WithRejection;
+use serde::{Deserialize, Serialize};
+
+use crate::server::{
+    routes::v1::{
+        middleware::auth::{require_auth_not_admin, CurrentUser},
+        response::{V1Error, V1Response, V1Result},
+    },
+    AppRouter,
+};
+
+pub(super) fn router() -> AppRouter {
+    Router::new()
+        .route("/", post(explain))
+        .route_layer(middleware::from_fn(require_auth_not_admin))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+struct ExplainPayload {
+    url: String,
+    #[serde(flatten)]
+    meta: Option<DownloadRequestMeta>,
+}
+/// Shows what a download request for the URL would do without downloading anything
+async fn explain(
+    Extension(user): Extension<CurrentUser>,
+    WithRejection(Json(payload), _): WithRejection<Json<ExplainPayload>, V1Error>,
+) -> V1Result<DownloadPlan> {
+    let url = url_resolves_to_valid_ip(&payload.url)
+        .map_err(|e| V1Response::error(StatusCode::BAD_REQUEST, e.to_string()))?;
+    let download_dir = user
+        .resolve_download_folder()
+        .map_err(|e| V1Response::error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let meta = payload.meta.unwrap_or_default();
+
+    let extract_request = ExtractInfoRequest::new(url)
+        .with_method(meta.request.method.clone())
+        .with_headers(meta.request.headers.clone())
+        .with_extractor_options(meta.extractor_options.clone())
+        .with_downloader_options(meta.downloader_options.clone())
+        .with_downloader_defaults(
+            YtDlp.name(),
+            YtDlp::options().with_quality(Some(QualityPreset::Best)),
+        );
+
+    let plan = explain_url(extract_request, &download_dir)
+        .await
+        .map_err(|e| V1Response::error(StatusCode::UNPROCESSABLE_ENTITY, e))?;
+
+    Ok(V1Response::success(plan))
+}
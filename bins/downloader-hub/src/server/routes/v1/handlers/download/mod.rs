@@ -4,9 +4,11 @@ use crate::server::AppRouter;
 
 mod download_request;
 mod download_result;
+mod explain;
 
 pub(super) fn router() -> AppRouter {
     Router::new()
         .nest("/requests", download_request::router())
         .nest("/results", download_result::router())
+        .nest("/explain", explain::router())
 }
@@ -109,6 +109,31 @@ impl DownloadRequestService {
             .await
     }
 
+    pub async fn update_post_metadata<TDb, TValue>(
+        db: &TDb,
+        uid: TValue,
+        post_metadata: Option<Json>,
+    ) -> Result<UpdateResult, DbErr>
+    where
+        TDb: ConnectionTrait,
+        TValue: Into<Value> + Send + Sync,
+    {
+        let model = {
+            let mut model = download_request::ActiveModel::new();
+
+            model.post_metadata = Set(post_metadata);
+            model.updated_at = Set(chrono::Utc::now().into());
+
+            model
+        };
+
+        download_request::Entity::update_many()
+            .set(model)
+            .filter(download_request::Column::RequestUid.eq(uid))
+            .exec(db)
+            .await
+    }
+
     pub async fn find_by_uid<TDb, TValue1>(
         db: &TDb,
         uid: TValue1,
@@ -212,6 +237,7 @@ impl From<DownloadRequestStatus> for ItemStatus {
 
 pub struct CreateDownloadRequestPayload {
     pub url: String,
+    pub canonical_url: Option<String>,
     pub client_id: i32,
     pub meta: Option<DownloadRequestMeta>,
     pub app_meta: Option<DownloadRequestAppMeta>,
@@ -221,6 +247,7 @@ impl CreateDownloadRequestPayload {
     pub fn into_active_model(self) -> download_request::ActiveModel {
         let mut request = download_request::ActiveModel {
             url: Set(self.url),
+            canonical_url: Set(self.canonical_url),
             request_uid: Set(AppUidFor::download_request()),
             client_id: Set(self.client_id),
             ..Default::default()
@@ -4,7 +4,10 @@ use app_entities::{
     download_result,
     entity_meta::{
         common::path::AppPath,
-        download_result::{DownloadResultMeta, DownloadResultMetaFileData, DownloadResultStatus},
+        download_result::{
+            DownloadResultMeta, DownloadResultMetaFileData, DownloadResultSource,
+            DownloadResultStatus, MEDIA_INFO_META_KEY, SOURCE_META_KEY,
+        },
     },
     sea_orm_active_enums::ItemStatusEnum,
 };
@@ -218,6 +221,8 @@ pub struct CreateDownloadResultPayload {
     pub status: DownloadResultStatus,
     pub path: Option<PathBuf>,
     pub meta: Option<DownloadResultMeta>,
+    pub media_info: Option<serde_json::Value>,
+    pub source: Option<DownloadResultSource>,
 }
 impl CreateDownloadResultPayload {
     pub fn into_active_model(self) -> download_result::ActiveModel {
@@ -229,7 +234,21 @@ impl CreateDownloadResultPayload {
             ..Default::default()
         };
 
-        if let Some(meta) = self.meta {
+        let mut meta = self
+            .meta
+            .map(serde_json::Value::from)
+            .and_then(|x| x.as_object().cloned())
+            .unwrap_or_default();
+
+        if let Some(media_info) = self.media_info {
+            meta.insert(MEDIA_INFO_META_KEY.to_string(), media_info);
+        }
+
+        if let Some(source) = self.source.and_then(|x| serde_json::to_value(x).ok()) {
+            meta.insert(SOURCE_META_KEY.to_string(), source);
+        }
+
+        if !meta.is_empty() {
             model.meta = Set(meta.into());
         }
 
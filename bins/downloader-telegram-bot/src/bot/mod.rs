@@ -5,6 +5,7 @@ use std::{collections::HashMap, string::ToString, sync::OnceLock};
 use app_actions::{
     actions::{handlers::ActionEntry, ActionOptions, AVAILABLE_ACTIONS},
     downloaders::AVAILABLE_DOWNLOADERS,
+    explain::explain_url,
     extractors::AVAILABLE_EXTRACTORS,
     fixers::{handlers::FixerInstance, AVAILABLE_FIXERS},
 };
@@ -14,14 +15,14 @@ use teloxide::{
     prelude::*,
     requests::RequesterExt,
     types::{LinkPreviewOptions, ParseMode, ReplyParameters},
-    utils::command::BotCommands,
+    utils::{command::BotCommands, html},
 };
 use tracing::{field, info, trace, Instrument, Span};
 use url::Url;
 
 use crate::{
     config::Config,
-    queue::{Task, TaskQueue},
+    queue::{common::urls::urls_in_message, Task, TaskQueue},
 };
 
 pub type TeloxideBot =
@@ -82,6 +83,11 @@ enum BotCommand {
         parse_with = parse_action,
     )]
     Act(ActionEntry, ActionOptions),
+    #[command(
+        description = "Show which extractor and downloaders would handle the links in the message \
+                       (or the one it replies to) without downloading anything."
+    )]
+    Explain(String),
 }
 
 struct CmdActParams(ActionEntry, ActionOptions);
@@ -398,11 +404,101 @@ async fn handle_command(msg: Message, command: BotCommand) -> ResponseResult<()>
 
             TaskQueue::push(Task::action_request(msg, action, options, status_message));
         }
+        BotCommand::Explain(text) => {
+            let mut urls = text
+                .split_whitespace()
+                .filter_map(|x| Url::parse(x).ok())
+                .collect::<Vec<_>>();
+            if urls.is_empty() {
+                urls = msg
+                    .reply_to_message()
+                    .map(urls_in_message)
+                    .unwrap_or_default();
+            }
+
+            let text = if urls.is_empty() {
+                "No links found. Send the links with the command or reply to a message with \
+                 links."
+                    .to_string()
+            } else {
+                explain_urls_text(&urls).await
+            };
+
+            TelegramBot::instance()
+                .send_message(msg.chat.id, text)
+                .reply_parameters(ReplyParameters::new(msg.id).allow_sending_without_reply())
+                .link_preview_options(LinkPreviewOptions {
+                    is_disabled: true,
+                    prefer_large_media: false,
+                    prefer_small_media: false,
+                    show_above_text: false,
+                    url: None,
+                })
+                .await?;
+        }
     }
 
     Ok(())
 }
 
+async fn explain_urls_text(urls: &[Url]) -> String {
+    let mut paragraphs = vec![];
+
+    for url in urls {
+        let mut lines = vec![format!("<b>{}</b>", html::escape(url.as_str()))];
+
+        match explain_url(url, &std::env::temp_dir()).await {
+            Ok(plan) => {
+                lines.push(format!(
+                    "Extractor: <u>{}</u>",
+                    plan.extractor.as_deref().unwrap_or("unknown")
+                ));
+
+                if plan.downloads.is_empty() {
+                    lines.push("No URLs to download".to_string());
+                }
+
+                for download in plan.downloads {
+                    let mut download_lines = vec![
+                        html::escape(download.url.as_str()),
+                        format!(
+                            "Downloader: <u>{}</u>",
+                            download
+                                .downloader
+                                .as_deref()
+                                .unwrap_or("none can download this URL")
+                        ),
+                    ];
+
+                    if let Some(preferred) = download.preferred_downloader {
+                        download_lines.push(format!("Preferred downloader: <u>{preferred}</u>"));
+                    }
+
+                    if !download.downloader_options.is_empty() {
+                        download_lines.push(format!(
+                            "Options: <code>{}</code>",
+                            html::escape(
+                                &serde_json::to_string(&download.downloader_options)
+                                    .unwrap_or_default()
+                            )
+                        ));
+                    }
+
+                    lines.push(format!(
+                        "<blockquote>{}</blockquote>",
+                        download_lines.join("\n")
+                    ));
+                }
+            }
+            Err(e) => lines.push(format!("Error: {}", html::escape(&e))),
+        }
+
+        paragraphs.push(lines.join("\n"));
+    }
+
+    paragraphs.join("\n\n")
+}
+
 async fn handle_message(msg: Message) -> ResponseResult<()> {
     info!("Adding download request to queue");
 
@@ -1,3 +1,4 @@
 pub mod file;
 pub mod file_group;
+pub mod post_caption;
 pub mod urls;
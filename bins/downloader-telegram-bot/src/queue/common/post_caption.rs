@@ -0,0 +1,65 @@
+use app_actions::extractors::PostMetadata;
+use teloxide::utils::html;
+
+/// Telegram limits media captions to 1024 characters
+pub const MAX_CAPTION_LENGTH: usize = 1024;
+
+/// Builds a caption for the given posts, truncated to fit in a Telegram caption.
+///
+/// The caption is HTML escaped after truncating, so no entity gets cut in half
+/// and the limit still applies to the text Telegram shows.
+pub fn post_metadata_caption(posts: &[PostMetadata]) -> Option<String> {
+    let caption = posts
+        .iter()
+        .map(post_caption)
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    if caption.is_empty() {
+        return None;
+    }
+
+    Some(html::escape(&truncate(&caption, MAX_CAPTION_LENGTH)))
+}
+
+fn post_caption(post: &PostMetadata) -> String {
+    let author = match (&post.author_display_name, &post.author_handle) {
+        (Some(name), Some(handle)) => Some(format!("{name} (@{handle})")),
+        (Some(name), None) => Some(name.clone()),
+        (None, Some(handle)) => Some(format!("@{handle}")),
+        (None, None) => None,
+    };
+
+    let footer = [
+        post.created_at
+            .map(|x| x.format("%Y-%m-%d %H:%M UTC").to_string()),
+        post.source_url.clone(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join("\n");
+
+    [
+        author,
+        post.text.as_ref().map(|x| x.trim().to_string()),
+        Some(footer),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|x| !x.is_empty())
+    .collect::<Vec<_>>()
+    .join("\n\n")
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let mut truncated = text.chars().take(max_chars - 1).collect::<String>();
+    truncated.push('…');
+
+    truncated
+}
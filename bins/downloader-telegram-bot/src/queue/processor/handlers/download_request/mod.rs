@@ -1,6 +1,14 @@
 use std::path::{Path, PathBuf};
 
-use app_actions::{download_file, fix_file};
+use app_actions::{
+    download_file,
+    downloaders::{
+        handlers::yt_dlp::{QualityPreset, YtDlp},
+        Downloader,
+    },
+    extractors::{ExtractInfoRequest, PostMetadata},
+    fix_file,
+};
 use app_helpers::temp_dir::TempDir;
 use futures::{stream::FuturesUnordered, StreamExt};
 use teloxide::types::Message;
@@ -11,7 +19,7 @@ use super::{Handler, HandlerError, HandlerReturn};
 use crate::{
     config::Config,
     queue::{
-        common::{file::FileId, urls::urls_in_message},
+        common::{file::FileId, post_caption::post_metadata_caption, urls::urls_in_message},
         task::{Task, TaskInfo},
     },
 };
@@ -50,7 +58,8 @@ impl Handler for DownloadRequestHandler {
         ))?;
 
         debug!("Downloading files");
-        let paths_to_fix = download_files(temp_download_dir.path(), task, msg).await?;
+        let (paths_to_fix, post_metadata) =
+            download_files(temp_download_dir.path(), task, msg).await?;
         debug!("Downloaded files");
         trace!(?paths_to_fix, "Downloaded files");
 
@@ -84,7 +93,13 @@ impl Handler for DownloadRequestHandler {
             }
         }
 
-        task.reply_with_files(fixed_file_paths)
+        let caption = if Config::bot().caption_with_post_metadata {
+            post_metadata_caption(&post_metadata)
+        } else {
+            None
+        };
+
+        task.reply_with_captioned_files(fixed_file_paths, caption)
             .await
             .map_err(HandlerError::Fatal)?;
 
@@ -170,17 +185,18 @@ async fn download_files(
     download_dir: &Path,
     task: &Task,
     msg: &Message,
-) -> Result<Vec<PathBuf>, HandlerError> {
+) -> Result<(Vec<PathBuf>, Vec<PostMetadata>), HandlerError> {
     let file_id = FileId::from_message(msg);
     let file_urls = urls_in_message(msg);
 
     if file_id.is_none() && file_urls.is_empty() {
-        return Ok(vec![]);
+        return Ok((vec![], vec![]));
     }
 
     trace!(?file_id, ?file_urls, "Found message parts to process");
 
     let mut paths_to_fix = vec![];
+    let mut post_metadata = vec![];
 
     if let Some(file_id) = file_id {
         debug!(?file_id, "Downloading file from telegram");
@@ -204,7 +220,7 @@ async fn download_files(
 
         trace!(?file_urls, "Downloading files from URLs");
 
-        let (downloaded_file_paths, download_errors) =
+        let (downloaded_file_paths, download_errors, downloaded_post_metadata) =
             download_files_from_urls(&file_urls, download_dir).await;
 
         for error in download_errors {
@@ -214,20 +230,27 @@ async fn download_files(
         trace!(?downloaded_file_paths, "Downloaded files from URLs");
 
         paths_to_fix.extend(downloaded_file_paths);
+        post_metadata.extend(downloaded_post_metadata);
     }
 
-    Ok(paths_to_fix)
+    Ok((paths_to_fix, post_metadata))
 }
 
 #[tracing::instrument(skip_all, fields(download_dir))]
 async fn download_files_from_urls(
     file_urls: &[Url],
     download_dir: &Path,
-) -> (Vec<PathBuf>, Vec<String>) {
+) -> (Vec<PathBuf>, Vec<String>, Vec<PostMetadata>) {
     let results = file_urls
         .iter()
         .map(|url| async move {
-            let res = download_file(url, download_dir).await;
+            // Prefer formats that can be uploaded to Telegram as is
+            let request = ExtractInfoRequest::from(url).with_downloader_defaults(
+                YtDlp.name(),
+                YtDlp::options().with_quality(Some(QualityPreset::TelegramFriendly)),
+            );
+
+            let res = download_file(request, download_dir).await;
 
             (url.to_string(), res)
         })
@@ -237,6 +260,7 @@ async fn download_files_from_urls(
 
     let mut downloaded_paths = vec![];
     let mut errors = vec![];
+    let mut post_metadata = vec![];
     for (url, url_results) in &results {
         let errs = url_results
             .iter()
@@ -263,7 +287,15 @@ async fn download_files_from_urls(
             .map(|x| x.path.clone());
 
         downloaded_paths.extend(paths);
+
+        // All files downloaded from the same URL share the post metadata
+        let url_post_metadata = url_results
+            .iter()
+            .filter_map(|x| x.as_ref().ok())
+            .find_map(|x| x.request.post_metadata.clone());
+
+        post_metadata.extend(url_post_metadata);
     }
 
-    (downloaded_paths, errors)
+    (downloaded_paths, errors, post_metadata)
 }
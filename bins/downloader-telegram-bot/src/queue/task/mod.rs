@@ -6,7 +6,7 @@ use app_actions::{
 };
 use teloxide::{
     prelude::*,
-    types::{Message, ReplyParameters},
+    types::{InputMedia, Message, ReplyParameters},
 };
 use tracing::{debug, field, trace, warn, Span};
 
@@ -74,11 +74,25 @@ impl Task {
 impl Task {
     #[tracing::instrument(skip_all)]
     pub async fn reply_with_files(&self, paths: Vec<PathBuf>) -> Result<(), String> {
+        self.reply_with_captioned_files(paths, None).await
+    }
+
+    /// The caption is added to the first file of the first media group.
+    #[tracing::instrument(skip_all)]
+    pub async fn reply_with_captioned_files(
+        &self,
+        paths: Vec<PathBuf>,
+        caption: Option<String>,
+    ) -> Result<(), String> {
         trace!("Chunking files by size");
-        let (media_groups, failed_files) =
+        let (mut media_groups, failed_files) =
             files_to_input_media_groups(paths, MAX_PAYLOAD_SIZE_BYTES / 10 * 8).await;
         trace!(?media_groups, ?failed_files, "Chunked files by size");
 
+        if let Some(media) = media_groups.first_mut().and_then(|x| x.first_mut()) {
+            set_media_caption(media, caption);
+        }
+
         debug!("Uploading files to Telegram");
         for media_group in media_groups {
             trace!(?media_group, "Uploading media group");
@@ -206,6 +220,16 @@ impl Task {
     }
 }
 
+fn set_media_caption(media: &mut InputMedia, caption: Option<String>) {
+    match media {
+        InputMedia::Photo(x) => x.caption = caption,
+        InputMedia::Video(x) => x.caption = caption,
+        InputMedia::Animation(x) => x.caption = caption,
+        InputMedia::Audio(x) => x.caption = caption,
+        InputMedia::Document(x) => x.caption = caption,
+    }
+}
+
 async fn try_and_warn<F, T, E, S>(f: F, error_msg: S) -> Option<T>
 where
     F: std::future::Future<Output = Result<T, E>> + Send,
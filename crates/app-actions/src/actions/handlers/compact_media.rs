@@ -40,6 +40,7 @@ impl Action for CompactMedia {
         trace!("Output file path: {output_file_path:?}");
 
         let mut cmd = tokio::process::Command::new("ffmpeg");
+        cmd.kill_on_drop(true);
         cmd.arg("-i")
             .arg(&request.file_path)
             .args(["-max_muxing_queue_size", "1024"])
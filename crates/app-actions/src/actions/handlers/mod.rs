@@ -2,6 +2,7 @@ pub mod compact_media;
 pub mod file_rename_to_id;
 pub mod ocr_image;
 pub mod remove_background;
+pub mod render_slideshow;
 pub mod split_scenes;
 
 use std::sync::{Arc, LazyLock};
@@ -24,6 +25,7 @@ fn all_actions() -> Vec<ActionEntry> {
         Arc::new(compact_media::CompactMedia),
         Arc::new(ocr_image::OcrImage),
         Arc::new(remove_background::RemoveBackground),
+        Arc::new(render_slideshow::RenderSlideshow),
     ]
 }
 
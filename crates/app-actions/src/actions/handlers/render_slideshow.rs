@@ -0,0 +1,178 @@
+use std::{
+    ffi::OsString,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use app_helpers::{
+    file_name::file_name_with_suffix,
+    file_type::{infer_file_type, mime},
+};
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+
+use crate::{
+    actions::{Action, ActionError, ActionRequest, ActionResult},
+    config::ActionsConfig,
+};
+
+const DEFAULT_SECONDS_PER_IMAGE: f64 = 3.0;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RenderSlideshow;
+
+#[async_trait::async_trait]
+#[typetag::serde]
+impl Action for RenderSlideshow {
+    fn description(&self) -> &'static str {
+        "Render a list of images and an audio track (eg. a TikTok slideshow) into a video."
+    }
+
+    async fn can_run_for(&self, req: &ActionRequest) -> bool {
+        let Some(options) = req.options::<RenderSlideshowOptions>() else {
+            return false;
+        };
+
+        if options.images.is_empty() {
+            return false;
+        }
+
+        let file_mime = {
+            let file_path = req.file_path.clone();
+            tokio::task::spawn_blocking(move || infer_file_type(&file_path)).await
+        };
+
+        matches!(file_mime, Ok(Ok(x)) if x.type_() == mime::AUDIO)
+    }
+
+    /// The request file is the audio track.
+    ///
+    /// Options:
+    /// - `images`: The paths of the images, in the order they should be shown.
+    /// - `seconds-per-image`: How long each image is shown. Defaults to 3 seconds.
+    async fn run(&self, request: &ActionRequest) -> Result<ActionResult, ActionError> {
+        trace!("Running render slideshow action");
+
+        let options = request
+            .options::<RenderSlideshowOptions>()
+            .filter(|x| !x.images.is_empty())
+            .ok_or_else(|| ActionError::FailedAction("No images given for the slideshow".into()))?;
+
+        let output_file_path = request
+            .output_dir
+            .join(file_name_with_suffix(&request.file_path, "slideshow"))
+            .with_extension("mp4");
+
+        trace!("Output file path: {output_file_path:?}");
+
+        let seconds_per_image = options
+            .seconds_per_image
+            .filter(|x| *x > 0.0)
+            .unwrap_or(DEFAULT_SECONDS_PER_IMAGE);
+
+        let images = checked_images(&options.images).await?;
+
+        #[allow(clippy::cast_precision_loss)]
+        let total_duration = seconds_per_image * images.len() as f64;
+
+        let mut cmd = tokio::process::Command::new(ActionsConfig::dependency_paths().ffmpeg_path());
+        cmd.kill_on_drop(true);
+        cmd.arg("-y");
+
+        // Every image is its own input so no file list has to be parsed by ffmpeg.
+        // The `file:` prefix keeps ffmpeg from treating the paths as other protocols.
+        for image in &images {
+            cmd.args(["-loop", "1"])
+                .args(["-t", &seconds_per_image.to_string()])
+                .arg("-i")
+                .arg(file_input(image));
+        }
+
+        cmd.arg("-i")
+            .arg(file_input(&request.file_path))
+            .args(["-filter_complex", &filter_graph(images.len())])
+            .args(["-map", "[v]"])
+            .args(["-map", &format!("{}:a:0", images.len())])
+            .args(["-c:v", "libx264"])
+            .args(["-c:a", "aac"])
+            .args(["-b:a", "192k"])
+            .args(["-t", &total_duration.to_string()])
+            .args(["-movflags", "+faststart"])
+            .arg(&output_file_path);
+
+        trace!("Running command: {cmd:?}");
+
+        let output = cmd.output().await.map_err(|e| {
+            ActionError::FailedAction(format!("Failed to run ffmpeg: {e:?}").into())
+        })?;
+
+        trace!("Command output: {output:?}");
+
+        if !output.status.success() {
+            return Err(ActionError::FailedAction(
+                format!("ffmpeg exited with error code {output:?}").into(),
+            ));
+        }
+
+        Ok(ActionResult::path(request, output_file_path))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RenderSlideshowOptions {
+    #[serde(default)]
+    pub images: Vec<PathBuf>,
+    #[serde(default)]
+    pub seconds_per_image: Option<f64>,
+}
+
+/// Makes sure all the images are existing image files
+async fn checked_images(images: &[PathBuf]) -> Result<Vec<PathBuf>, ActionError> {
+    let mut checked = vec![];
+
+    for image in images {
+        let path = tokio::fs::canonicalize(image).await.map_err(|e| {
+            ActionError::FailedAction(format!("Failed to find image {image:?}: {e:?}").into())
+        })?;
+
+        let file_mime = {
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || infer_file_type(&path)).await
+        };
+
+        if !path.is_file() || !matches!(file_mime, Ok(Ok(x)) if x.type_() == mime::IMAGE) {
+            return Err(ActionError::FailedAction(
+                format!("{image:?} is not an image file").into(),
+            ));
+        }
+
+        checked.push(path);
+    }
+
+    Ok(checked)
+}
+
+fn file_input(path: &Path) -> OsString {
+    let mut input = OsString::from("file:");
+    input.push(path);
+    input
+}
+
+/// Scales every image to the same size and joins them one after another
+fn filter_graph(image_count: usize) -> String {
+    let scale = "scale=w=1080:h=1920:force_original_aspect_ratio=decrease,pad=1080:1920:(ow-iw)/2:\
+                 (oh-ih)/2,setsar=1,fps=30,format=yuv420p";
+
+    let mut parts = (0..image_count)
+        .map(|i| format!("[{i}:v]{scale}[v{i}]"))
+        .collect::<Vec<_>>();
+
+    let inputs = (0..image_count).fold(String::new(), |mut acc, i| {
+        let _ = write!(acc, "[v{i}]");
+        acc
+    });
+    parts.push(format!("{inputs}concat=n={image_count}:v=1:a=0[v]"));
+
+    parts.join(";")
+}
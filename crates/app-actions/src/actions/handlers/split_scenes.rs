@@ -82,6 +82,7 @@ async fn split_into_scenes(config: SplitVideoConfig) -> Result<Vec<PathBuf>, Act
         .map_err(|x| SplitScenesError::TempDirCreate(x.into()))?;
 
     let mut cmd = Command::new(scenedetect_path);
+    cmd.kill_on_drop(true);
     let cmd = cmd
         .args(["--input", config.file_path.to_str().unwrap_or_default()])
         .arg("detect-adaptive")
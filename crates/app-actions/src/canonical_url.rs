@@ -0,0 +1,101 @@
+use url::Url;
+
+/// Alternative hosts (mirrors, mobile versions, old domains) and the host they stand in for
+const HOST_ALIASES: &[(&str, &str)] = &[
+    ("twitter.com", "x.com"),
+    ("www.twitter.com", "x.com"),
+    ("mobile.twitter.com", "x.com"),
+    ("m.twitter.com", "x.com"),
+    ("www.x.com", "x.com"),
+    ("mobile.x.com", "x.com"),
+    ("vxtwitter.com", "x.com"),
+    ("www.vxtwitter.com", "x.com"),
+    ("fxtwitter.com", "x.com"),
+    ("www.fxtwitter.com", "x.com"),
+    ("fixupx.com", "x.com"),
+    ("fixvx.com", "x.com"),
+    ("twittpr.com", "x.com"),
+    ("youtube.com", "www.youtube.com"),
+    ("m.youtube.com", "www.youtube.com"),
+    ("facebook.com", "www.facebook.com"),
+    ("m.facebook.com", "www.facebook.com"),
+    ("tiktok.com", "www.tiktok.com"),
+    ("m.tiktok.com", "www.tiktok.com"),
+    ("reddit.com", "www.reddit.com"),
+    ("m.reddit.com", "www.reddit.com"),
+    ("old.reddit.com", "www.reddit.com"),
+    ("new.reddit.com", "www.reddit.com"),
+    ("instagram.com", "www.instagram.com"),
+    ("ddinstagram.com", "www.instagram.com"),
+    ("www.ddinstagram.com", "www.instagram.com"),
+    ("threads.net", "www.threads.com"),
+    ("www.threads.net", "www.threads.com"),
+    ("threads.com", "www.threads.com"),
+    ("www.imgur.com", "imgur.com"),
+    ("m.imgur.com", "imgur.com"),
+    ("www.9gag.com", "9gag.com"),
+    ("m.9gag.com", "9gag.com"),
+];
+
+/// Query parameters that are only used for tracking and never change the content
+const TRACKING_PARAMS: &[&str] = &["si", "igshid", "igsh", "fbclid", "gclid", "ref_src"];
+
+/// Query parameters that are only used for tracking on X/Twitter
+const TWITTER_TRACKING_PARAMS: &[&str] = &["s", "t"];
+
+/// Normalizes the URL so the same content shared in different ways gets the same URL.
+/// Maps mirror and mobile hosts to the main one and strips tracking parameters.
+#[must_use]
+pub fn canonicalize_url(url: &Url) -> Url {
+    let mut url = url.clone();
+
+    if let Some(canonical_host) = url.host_str().and_then(canonical_host) {
+        let _ = url.set_host(Some(canonical_host));
+    }
+
+    strip_tracking_params(&mut url);
+
+    url
+}
+
+fn canonical_host(host: &str) -> Option<&'static str> {
+    HOST_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == host)
+        .map(|(_, canonical)| *canonical)
+}
+
+fn is_tracking_param(url: &Url, key: &str) -> bool {
+    if key.starts_with("utm_") || TRACKING_PARAMS.contains(&key) {
+        return true;
+    }
+
+    let is_twitter = url
+        .host_str()
+        .is_some_and(|x| x == "x.com" || canonical_host(x) == Some("x.com"));
+
+    is_twitter && TWITTER_TRACKING_PARAMS.contains(&key)
+}
+
+pub fn strip_tracking_params(url: &mut Url) {
+    let Some(query) = url.query() else {
+        return;
+    };
+
+    let params = url
+        .query_pairs()
+        .filter(|(k, _)| !is_tracking_param(url, k))
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect::<Vec<_>>();
+
+    // Only touch the query if something was removed so the original encoding is kept
+    if params.len() == form_urlencoded::parse(query.as_bytes()).count() {
+        return;
+    }
+
+    if params.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(params);
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    fmt,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use http::{header, HeaderMap, HeaderName, HeaderValue};
+use tracing::{debug, warn};
+use url::Url;
+
+/// Cookies and headers loaded from the credentials directory, matched to requests by domain.
+///
+/// Values are marked as sensitive and never printed.
+#[derive(Clone, Default)]
+pub struct CredentialStore {
+    cookies: Vec<Cookie>,
+    headers: Vec<(String, HeaderMap)>,
+}
+impl fmt::Debug for CredentialStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CredentialStore")
+            .field("cookies", &self.cookies.len())
+            .field(
+                "headers",
+                &self.headers.iter().map(|(x, _)| x).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+struct Cookie {
+    domain: String,
+    include_subdomains: bool,
+    path: String,
+    secure: bool,
+    expires: u64,
+    name: String,
+    value: String,
+    http_only: bool,
+}
+
+impl CredentialStore {
+    pub fn from_dir(dir: &Path) -> Result<Self, String> {
+        let mut store = Self::default();
+
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read credentials directory: {e:?}"))?;
+
+        for entry in entries {
+            let path = entry
+                .map_err(|e| format!("Failed to read credentials directory: {e:?}"))?
+                .path();
+
+            if !path.is_file() {
+                continue;
+            }
+
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read credentials file {path:?}: {e:?}"))?;
+
+            if path.extension().is_some_and(|x| x == "headers") {
+                let Some(domain) = path.file_stem().and_then(|x| x.to_str()) else {
+                    continue;
+                };
+
+                let headers = parse_headers(&contents);
+                debug!(
+                    ?path,
+                    ?domain,
+                    count = headers.len(),
+                    "Loaded credential headers"
+                );
+
+                store
+                    .headers
+                    .push((normalize_domain(domain).to_string(), headers));
+            } else {
+                let cookies = parse_cookies(&contents);
+                debug!(?path, count = cookies.len(), "Loaded credential cookies");
+
+                store.cookies.extend(cookies);
+            }
+        }
+
+        Ok(store)
+    }
+
+    /// Whether there are stored headers for the URL's domain
+    #[must_use]
+    pub fn has_headers_for(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+
+        self.headers
+            .iter()
+            .any(|(domain, headers)| !headers.is_empty() && domain_matches(host, domain, true))
+    }
+
+    /// Returns `headers` with the stored credentials for the URL added.
+    ///
+    /// Headers that are already set take precedence over the stored ones.
+    #[must_use]
+    pub fn apply(&self, url: &Url, headers: &HeaderMap) -> HeaderMap {
+        let mut headers = headers.clone();
+        let Some(host) = url.host_str() else {
+            return headers;
+        };
+
+        for (_, domain_headers) in self
+            .headers
+            .iter()
+            .filter(|(domain, _)| domain_matches(host, domain, true))
+        {
+            for (k, v) in domain_headers {
+                if !headers.contains_key(k) {
+                    headers.insert(k, v.clone());
+                }
+            }
+        }
+
+        let existing_cookies = headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|x| x.to_str().ok())
+            .flat_map(|x| x.split(';'))
+            .filter_map(|x| x.split_once('=').map(|(k, _)| k.trim().to_string()))
+            .collect::<Vec<_>>();
+
+        let cookies = self
+            .cookies_for(url)
+            .filter(|x| !existing_cookies.contains(&x.name))
+            .map(|x| format!("{}={}", x.name, x.value))
+            .collect::<Vec<_>>();
+
+        if !cookies.is_empty() {
+            if let Ok(mut value) = HeaderValue::from_str(&cookies.join("; ")) {
+                value.set_sensitive(true);
+                headers.append(header::COOKIE, value);
+            }
+        }
+
+        headers
+    }
+
+    /// Stored cookies for the URL in the Netscape cookie file format
+    #[must_use]
+    pub fn netscape_cookie_lines(&self, url: &Url) -> Vec<String> {
+        self.cookies_for(url)
+            .map(|x| {
+                format!(
+                    "{http_only}{domain}\t{subdomains}\t{path}\t{secure}\t{expires}\t{name}\t{value}",
+                    http_only = if x.http_only { "#HttpOnly_" } else { "" },
+                    domain = x.domain,
+                    subdomains = netscape_bool(x.include_subdomains),
+                    path = x.path,
+                    secure = netscape_bool(x.secure),
+                    expires = x.expires,
+                    name = x.name,
+                    value = x.value,
+                )
+            })
+            .collect()
+    }
+
+    fn cookies_for<'a>(&'a self, url: &'a Url) -> impl Iterator<Item = &'a Cookie> {
+        let host = url.host_str().unwrap_or_default();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |x| x.as_secs());
+
+        self.cookies.iter().filter(move |x| {
+            !host.is_empty()
+                && domain_matches(host, &x.domain, x.include_subdomains)
+                && url.path().starts_with(&x.path)
+                && (!x.secure || url.scheme() == "https")
+                && (x.expires == 0 || x.expires > now)
+        })
+    }
+}
+
+fn normalize_domain(domain: &str) -> &str {
+    domain.trim().trim_start_matches('.')
+}
+
+fn domain_matches(host: &str, domain: &str, include_subdomains: bool) -> bool {
+    let domain = normalize_domain(domain);
+
+    host.eq_ignore_ascii_case(domain)
+        || (include_subdomains
+            && host.len() > domain.len()
+            && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain)
+            && host.as_bytes()[host.len() - domain.len() - 1] == b'.')
+}
+
+const fn netscape_bool(value: bool) -> &'static str {
+    if value {
+        "TRUE"
+    } else {
+        "FALSE"
+    }
+}
+
+fn parse_headers(contents: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((name, value)) = line.split_once(':') else {
+            warn!("Skipping invalid credential header line");
+            continue;
+        };
+
+        let (Ok(name), Ok(mut value)) = (
+            HeaderName::try_from(name.trim()),
+            HeaderValue::from_str(value.trim()),
+        ) else {
+            warn!("Skipping invalid credential header line");
+            continue;
+        };
+
+        value.set_sensitive(true);
+        headers.append(name, value);
+    }
+
+    headers
+}
+
+fn parse_cookies(contents: &str) -> Vec<Cookie> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+                Some(line) => (line, true),
+                None if line.starts_with('#') => return None,
+                None => (line, false),
+            };
+
+            let parts = line.split('\t').collect::<Vec<_>>();
+            let [domain, include_subdomains, path, secure, expires, name, value] = parts[..] else {
+                return None;
+            };
+
+            Some(Cookie {
+                domain: domain.to_string(),
+                include_subdomains: include_subdomains.eq_ignore_ascii_case("TRUE")
+                    || domain.starts_with('.'),
+                path: path.to_string(),
+                secure: secure.eq_ignore_ascii_case("TRUE"),
+                expires: expires.parse().unwrap_or_default(),
+                name: name.to_string(),
+                value: value.to_string(),
+                http_only,
+            })
+        })
+        .collect()
+}
@@ -1,13 +1,17 @@
 use std::time::Duration;
 
+use reqwest::{redirect::Policy, Proxy};
 pub use reqwest::{Client as RequestClient, ClientBuilder as RequestClientBuilder, RequestBuilder};
 
 use super::url::UrlWithMeta;
+use crate::config::ActionsConfig;
 
 pub const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like \
                               Gecko) Chrome/88.0.4324.182 Safari/537.36";
 
 const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// Same as the default redirect policy of reqwest
+const MAX_REDIRECTS: usize = 10;
 
 pub struct Client;
 
@@ -18,19 +22,75 @@ impl Client {
             .map_err(|e| format!("Failed to create client: {:?}", e))
     }
 
-    pub fn base_with_url(url: &UrlWithMeta) -> Result<RequestBuilder, String> {
-        let mut builder = Self::base()?.request(url.method().clone(), url.url().as_str());
-
-        for (k, v) in url.headers() {
-            builder = builder.header(k, v);
-        }
+    /// Client for downloading files.
+    ///
+    /// Instead of limiting the whole request, it only times out if the connection stalls
+    /// so that large files on slow hosts can finish.
+    /// The body is not decompressed so that byte ranges line up with the file.
+    pub fn download() -> Result<RequestClient, String> {
+        with_proxy(RequestClient::builder())
+            .user_agent(USER_AGENT)
+            .redirect(redirect_policy())
+            .connect_timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+            .read_timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+            .no_gzip()
+            .no_brotli()
+            .no_deflate()
+            .build()
+            .map_err(|e| format!("Failed to create client: {:?}", e))
+    }
 
-        Ok(builder)
+    /// Also adds the stored credentials for the URL's domain.
+    pub fn with_url(client: &RequestClient, url: &UrlWithMeta) -> RequestBuilder {
+        client
+            .request(url.method().clone(), url.url().as_str())
+            .headers(ActionsConfig::credentials().apply(url.url(), url.headers()))
     }
 
     pub fn builder() -> RequestClientBuilder {
-        RequestClient::builder()
+        with_proxy(RequestClient::builder())
             .user_agent(USER_AGENT)
+            .redirect(redirect_policy())
             .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
     }
 }
+
+/// reqwest only drops the `Authorization` and `Cookie` headers when redirecting to another host,
+/// so don't follow those redirects if stored credential headers were added to the request.
+fn redirect_policy() -> Policy {
+    Policy::custom(|attempt| {
+        if attempt.previous().len() > MAX_REDIRECTS {
+            return attempt.error("Too many redirects");
+        }
+
+        let leaves_credential_host = attempt.previous().first().is_some_and(|first| {
+            first.host_str() != attempt.url().host_str()
+                && ActionsConfig::credentials().has_headers_for(first)
+        });
+
+        if leaves_credential_host {
+            return attempt.error(
+                "Not following redirect to another host for a request with stored credentials",
+            );
+        }
+
+        attempt.follow()
+    })
+}
+
+/// Routes requests through the configured proxies.
+///
+/// Without any proxy configuration the system proxy settings are used as before.
+fn with_proxy(builder: RequestClientBuilder) -> RequestClientBuilder {
+    let config = ActionsConfig::proxy();
+
+    if !config.is_enabled() {
+        return builder;
+    }
+
+    builder.proxy(Proxy::custom(move |url| {
+        url.host_str()
+            .and_then(|host| config.proxy_for(host))
+            .cloned()
+    }))
+}
@@ -1,3 +1,4 @@
+use app_helpers::ip::url_resolves_to_valid_ip;
 use http::{header::IntoHeaderName, HeaderMap, HeaderValue, Method};
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -91,3 +92,16 @@ impl PartialOrd for UrlWithMeta {
 const fn default_get() -> Method {
     Method::GET
 }
+
+/// Makes sure the URL is http(s) and doesn't point to an internal address.
+///
+/// Should be used for every URL that comes from a page or API response
+/// before fetching it or handing it to the downloaders.
+pub async fn validate_public_url(url: &str) -> Result<Url, String> {
+    let url_str = url.to_string();
+
+    tokio::task::spawn_blocking(move || url_resolves_to_valid_ip(&url_str))
+        .await
+        .map_err(|e| format!("Failed to validate URL: {e:?}"))?
+        .map_err(|e| format!("Invalid URL {url:?}: {e}"))
+}
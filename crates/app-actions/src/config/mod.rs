@@ -1,11 +1,13 @@
 use std::path::PathBuf;
 
 use app_config::{
-    common::{EndpointConfig, ProgramPathConfig, ProjectConfig},
+    common::{CredentialsConfig, EndpointConfig, ProgramPathConfig, ProjectConfig, ProxyConfig},
     GlobalConfig,
 };
 use validator::Validate;
 
+use crate::common::credentials::CredentialStore;
+
 #[derive(Debug, Clone, Default, Validate, GlobalConfig)]
 pub(crate) struct ActionsConfig {
     #[validate(nested)]
@@ -13,6 +15,10 @@ pub(crate) struct ActionsConfig {
 
     #[validate(nested)]
     pub dependency_paths: ProgramPathConfig,
+
+    pub credentials: CredentialStore,
+
+    pub proxy: ProxyConfig,
 }
 
 impl ActionsConfig {
@@ -28,6 +34,18 @@ impl ActionsConfig {
         &Self::global().dependency_paths
     }
 
+    #[must_use]
+    #[inline]
+    pub fn credentials() -> &'static CredentialStore {
+        &Self::global().credentials
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn proxy() -> &'static ProxyConfig {
+        &Self::global().proxy
+    }
+
     #[must_use]
     #[inline]
     pub fn cache_dir() -> PathBuf {
@@ -35,10 +53,22 @@ impl ActionsConfig {
     }
 }
 
-pub fn init(endpoint: EndpointConfig, dependency_paths: ProgramPathConfig) -> Result<(), String> {
+pub fn init(
+    endpoint: EndpointConfig,
+    dependency_paths: ProgramPathConfig,
+    credentials: CredentialsConfig,
+    proxy: ProxyConfig,
+) -> Result<(), String> {
+    let credentials = match credentials.credentials_dir {
+        Some(dir) => CredentialStore::from_dir(&dir)?,
+        None => CredentialStore::default(),
+    };
+
     ActionsConfig::init(ActionsConfig {
         endpoint,
         dependency_paths,
+        credentials,
+        proxy,
     })?;
 
     Ok(())
@@ -8,7 +8,7 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use crate::{
     common::url::UrlWithMeta,
     downloaders::DownloaderEntry,
-    extractors::{ExtractedInfo, ExtractedUrlInfo},
+    extractors::{ExtractedInfo, ExtractedUrlInfo, PostMetadata},
 };
 
 pub type DownloaderOptions = HashMap<String, serde_json::Value>;
@@ -19,6 +19,15 @@ pub struct DownloadRequest {
     pub download_dir: PathBuf,
     pub preferred_downloader: Option<DownloaderEntry>,
     pub downloader_options: DownloaderOptions,
+    /// Metadata of the post the URL was extracted from, if any
+    #[serde(default)]
+    pub post_metadata: Option<PostMetadata>,
+    /// Name of the extractor the URL came from, if any
+    #[serde(default)]
+    pub extractor: Option<String>,
+    /// Default options per downloader name, applied once the downloader is picked
+    #[serde(default)]
+    pub downloader_defaults: HashMap<String, DownloaderOptions>,
 }
 impl DownloadRequest {
     #[must_use]
@@ -31,6 +40,9 @@ impl DownloadRequest {
             download_dir: download_dir.to_path_buf(),
             preferred_downloader: None,
             downloader_options: HashMap::new(),
+            post_metadata: None,
+            extractor: None,
+            downloader_defaults: HashMap::new(),
         }
     }
 
@@ -41,6 +53,9 @@ impl DownloadRequest {
             download_dir: download_dir.to_path_buf(),
             preferred_downloader: info.preferred_downloader.clone(),
             downloader_options: info.downloader_options.clone(),
+            post_metadata: None,
+            extractor: None,
+            downloader_defaults: HashMap::new(),
         }
     }
 
@@ -48,7 +63,23 @@ impl DownloadRequest {
     pub fn from_extracted_info(info: &ExtractedInfo, download_dir: &Path) -> Vec<Self> {
         info.urls
             .iter()
-            .map(|x| Self::from_extracted_url(x, download_dir))
+            .map(|x| {
+                let mut request = Self::from_extracted_url(x, download_dir)
+                    .with_post_metadata(info.post_metadata.clone());
+                request.extractor.clone_from(&info.extractor);
+                request
+                    .downloader_defaults
+                    .clone_from(&info.request.downloader_defaults);
+
+                for (k, v) in &info.request.downloader_options {
+                    request
+                        .downloader_options
+                        .entry(k.clone())
+                        .or_insert_with(|| v.clone());
+                }
+
+                request
+            })
             .collect()
     }
 }
@@ -83,6 +114,25 @@ impl DownloadRequest {
         self
     }
 
+    #[must_use]
+    pub fn with_post_metadata(mut self, post_metadata: Option<PostMetadata>) -> Self {
+        self.post_metadata = post_metadata;
+        self
+    }
+
+    /// Fills in the default options of the downloader for the options that aren't set
+    #[must_use]
+    pub fn with_defaults_for(mut self, downloader: &str) -> Self {
+        if let Some(defaults) = self.downloader_defaults.get(downloader) {
+            for (k, v) in defaults {
+                self.downloader_options
+                    .entry(k.clone())
+                    .or_insert_with(|| v.clone());
+            }
+        }
+        self
+    }
+
     #[must_use]
     pub fn downloader_option_raw(&self, key: &str) -> Option<&serde_json::Value> {
         self.downloader_options.get(key)
@@ -2,10 +2,16 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
-use super::download_request::DownloadRequest;
+use super::{download_request::DownloadRequest, media_info::MediaInfo};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadResult {
     pub request: DownloadRequest,
     pub path: PathBuf,
+    /// Metadata about the media if the downloader provides it
+    #[serde(default)]
+    pub media_info: Option<MediaInfo>,
+    /// Name of the downloader that produced the file
+    #[serde(default)]
+    pub downloader: Option<String>,
 }
@@ -0,0 +1,31 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Information about the downloaded media as reported by the downloader.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MediaInfo {
+    /// The ID of the media on the site it was downloaded from
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub uploader: Option<String>,
+    pub uploader_id: Option<String>,
+    /// Duration in seconds
+    pub duration: Option<f64>,
+    pub upload_date: Option<NaiveDate>,
+    /// The URL of the page the media was downloaded from
+    pub original_url: Option<String>,
+    /// The name of the site or extractor that handled the URL (eg. `Youtube`)
+    pub source: Option<String>,
+    pub chapters: Vec<MediaChapter>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MediaChapter {
+    pub title: Option<String>,
+    /// Start time in seconds
+    pub start_time: f64,
+    /// End time in seconds
+    pub end_time: Option<f64>,
+}
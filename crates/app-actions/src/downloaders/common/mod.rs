@@ -1,2 +1,3 @@
 pub mod download_request;
 pub mod download_result;
+pub mod media_info;
@@ -1,22 +1,43 @@
-use std::{ffi::OsString, path::PathBuf, string::ToString};
+use std::{
+    ffi::OsString,
+    fs::TryLockError,
+    future::Future,
+    io::SeekFrom,
+    path::{Path, PathBuf},
+    string::ToString,
+};
 
 use app_config::timeframe::Timeframe;
 use app_helpers::id::time_id;
-use http::header;
+use http::{header, HeaderMap, StatusCode};
 use mime2ext::mime2ext;
+use reqwest::Response;
 use serde::{Deserialize, Serialize};
-use tokio::{fs::File, io::AsyncWriteExt};
+use sha2::{Digest, Sha256};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::{AsyncSeekExt, AsyncWriteExt},
+};
 use tracing::{debug, info, trace};
 use unicode_segmentation::UnicodeSegmentation;
 use url::Url;
 
-use super::{DownloadRequest, DownloadResult, Downloader, DownloaderReturn};
+use super::{segmented::Segmented, DownloadRequest, DownloadResult, Downloader, DownloaderReturn};
 use crate::{
-    common::request::Client,
-    downloaders::{helpers::headers::content_disposition, DownloaderOptions},
+    common::{
+        request::{Client, RequestClient},
+        url::UrlWithMeta,
+    },
+    downloaders::{
+        helpers::headers::{content_disposition, content_range},
+        DownloaderOptions,
+    },
 };
 
 pub const MAX_FILENAME_LENGTH: usize = 120;
+const MAX_RESUME_ATTEMPTS: u32 = 5;
+/// Smaller files aren't worth splitting into ranges
+const MIN_PARALLEL_RANGES_SIZE: u64 = 8 * 1024 * 1024;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Generic;
@@ -33,7 +54,12 @@ impl Downloader for Generic {
     }
 
     async fn download(&self, request: &DownloadRequest) -> DownloaderReturn {
-        self.download_one(request).await
+        // Saving the manifest itself is never what's wanted
+        if Segmented::is_manifest_url(request.url.url()) {
+            return Segmented.download(request).await;
+        }
+
+        self.download_one(request).await.map(|x| vec![x])
     }
 }
 
@@ -41,6 +67,8 @@ impl Downloader for Generic {
 #[serde(rename_all = "kebab-case")]
 pub struct GenericDownloaderOptions {
     timeout: Option<Timeframe>,
+    /// Download large files in this many ranges at the same time if the server supports it
+    parallel_ranges: Option<usize>,
 }
 impl GenericDownloaderOptions {
     #[must_use]
@@ -56,6 +84,12 @@ impl GenericDownloaderOptions {
         self.timeout = timeout.map(Into::into);
         self
     }
+
+    #[must_use]
+    pub const fn with_parallel_ranges(mut self, parallel_ranges: Option<usize>) -> Self {
+        self.parallel_ranges = parallel_ranges;
+        self
+    }
 }
 impl From<GenericDownloaderOptions> for DownloaderOptions {
     fn from(val: GenericDownloaderOptions) -> Self {
@@ -79,24 +113,37 @@ impl Generic {
         request_info: &DownloadRequest,
     ) -> Result<DownloadResult, String> {
         let url = &request_info.url;
-        let options = request_info.downloader_options::<GenericDownloaderOptions>();
+        let options = request_info
+            .downloader_options::<GenericDownloaderOptions>()
+            .unwrap_or_default();
 
         info!(?url, dir = ?request_info.download_dir(), "Downloading with generic downloader");
 
-        let mut res = Client::base_with_url(url)?.headers(url.headers().clone());
+        let client = Client::download()?;
+        let part_path = part_file_path(request_info.download_dir(), url.url());
+        // Held until the part file is moved to its final path
+        let _part_lock = lock_part_file(&part_path).await?;
+        debug!(?part_path, "Downloading to part file");
 
-        if let Some(timeout) = options.and_then(|x| x.timeout) {
-            res = res.timeout(timeout.into());
-        }
+        let mut attempt = 0;
+        let headers = loop {
+            let downloaded = part_file_len(&part_path).await;
 
-        let mut res = res
-            .send()
-            .await
-            .map_err(|e| format!("Failed to send request: {:?}", e))?
-            .error_for_status()
-            .map_err(|e| format!("Failed to get response: {:?}", e))?;
+            match download_to_part(&client, url, &options, &part_path).await {
+                Ok(headers) => break headers,
+                // Only keep trying while the download is making progress
+                Err(e)
+                    if attempt < MAX_RESUME_ATTEMPTS
+                        && part_file_len(&part_path).await > downloaded =>
+                {
+                    attempt += 1;
+                    debug!(?e, ?attempt, "Download interrupted, resuming");
+                }
+                Err(e) => return Err(e),
+            }
+        };
 
-        let mime_type = res.headers().get(header::CONTENT_TYPE).map(|x| x.to_str());
+        let mime_type = headers.get(header::CONTENT_TYPE).map(|x| x.to_str());
         debug!(?mime_type, "Got mime type");
         let mime_type = match mime_type {
             Some(Ok(mime_type)) => mime_type,
@@ -113,8 +160,7 @@ impl Generic {
 
         let taken_filename_len = id.len() + 1 + extension.len();
 
-        let req_file_name = res
-            .headers()
+        let req_file_name = headers
             .get(header::CONTENT_DISPOSITION)
             .and_then(|x| content_disposition::ContentDisposition::from_raw(x).ok())
             .and_then(|x| {
@@ -136,30 +182,300 @@ impl Generic {
         file_name.push(req_file_name);
 
         let file_path = request_info.download_dir().join(file_name);
-        debug!(?file_path, "Writing to file");
-        let mut out_file = File::create(&file_path)
+        debug!(?file_path, "Moving part file to final path");
+        fs::rename(&part_path, &file_path)
             .await
-            .map_err(|e| format!("Failed to create file: {:?}", e))?;
-
-        while let Some(chunk) = res
-            .chunk()
-            .await
-            .map_err(|e| format!("Failed to get chunk: {:?}", e))?
-        {
-            out_file
-                .write_all(&chunk)
-                .await
-                .map_err(|e| format!("Failed to write chunk: {:?}", e))?;
-        }
+            .map_err(|e| format!("Failed to move part file: {:?}", e))?;
 
         Ok(DownloadResult {
             request: request_info.clone(),
             path: file_path,
+            media_info: None,
+            downloader: Some(self.name().to_string()),
         })
     }
 }
 
-fn url_to_filename(url: &Url, taken_filename_len: usize) -> Option<String> {
+/// The part file is named after the URL so that a retried request continues where it stopped.
+fn part_file_path(download_dir: &Path, url: &Url) -> PathBuf {
+    let hash = hex::encode(Sha256::digest(url.as_str()));
+
+    download_dir.join(format!(".{}.part", &hash[..32]))
+}
+
+/// Concurrent downloads of the same URL into the same directory would share the part file,
+/// so wait for the other download to finish first.
+///
+/// The lock is released when the returned file is closed or the process exits.
+async fn lock_part_file(part_path: &Path) -> Result<std::fs::File, String> {
+    let hash = hex::encode(Sha256::digest(part_path.as_os_str().as_encoded_bytes()));
+    let lock_path = std::env::temp_dir().join(format!("downloader-part-{}.lock", &hash[..32]));
+
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|e| format!("Failed to open part file lock: {e:?}"))?;
+
+        match file.try_lock() {
+            Ok(()) => return Ok(file),
+            Err(TryLockError::WouldBlock) => {
+                debug!(
+                    ?lock_path,
+                    "Part file is in use, waiting for the other download"
+                );
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(format!("Failed to lock part file: {e:?}"));
+            }
+        }
+
+        file.lock()
+            .map_err(|e| format!("Failed to lock part file: {e:?}"))?;
+
+        Ok(file)
+    })
+    .await
+    .map_err(|e| format!("Failed to lock part file: {e:?}"))?
+}
+
+async fn part_file_len(path: &Path) -> u64 {
+    fs::metadata(path).await.map_or(0, |x| x.len())
+}
+
+fn send_request(
+    client: &RequestClient,
+    url: &UrlWithMeta,
+    options: &GenericDownloaderOptions,
+    range: Option<(u64, Option<u64>)>,
+) -> impl Future<Output = Result<Response, reqwest::Error>> {
+    let mut req = Client::with_url(client, url);
+
+    if let Some(timeout) = options.timeout {
+        req = req.timeout(timeout.into());
+    }
+
+    if let Some((start, end)) = range {
+        let end = end.map(|x| x.to_string()).unwrap_or_default();
+
+        req = req.header(header::RANGE, format!("bytes={start}-{end}"));
+    }
+
+    req.send()
+}
+
+/// Downloads the rest of the file into the part file and returns the headers of the response.
+async fn download_to_part(
+    client: &RequestClient,
+    url: &UrlWithMeta,
+    options: &GenericDownloaderOptions,
+    part_path: &Path,
+) -> Result<HeaderMap, String> {
+    let mut downloaded = part_file_len(part_path).await;
+
+    let mut res = send_request(
+        client,
+        url,
+        options,
+        (downloaded > 0).then_some((downloaded, None)),
+    )
+    .await
+    .map_err(|e| format!("Failed to send request: {:?}", e))?;
+
+    // The part file is stale (eg. the file changed on the server), so start over
+    if downloaded > 0 && res.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        debug!("Server can't resume from part file, starting over");
+        downloaded = 0;
+        res = send_request(client, url, options, None)
+            .await
+            .map_err(|e| format!("Failed to send request: {:?}", e))?;
+    }
+
+    let mut res = res
+        .error_for_status()
+        .map_err(|e| format!("Failed to get response: {:?}", e))?;
+
+    let content_range = res
+        .headers()
+        .get(header::CONTENT_RANGE)
+        .and_then(|x| content_range::ContentRange::from_raw(x).ok());
+
+    let (resumed, total_size) = match content_range {
+        Some(range) if res.status() == StatusCode::PARTIAL_CONTENT => {
+            if range.start != downloaded {
+                let _ = fs::remove_file(part_path).await;
+
+                return Err(format!(
+                    "Server resumed at byte {} instead of {}",
+                    range.start, downloaded
+                ));
+            }
+
+            (true, range.total)
+        }
+        _ => (false, res.content_length()),
+    };
+    debug!(?resumed, ?downloaded, ?total_size, "Got response");
+
+    let supports_ranges = res
+        .headers()
+        .get(header::ACCEPT_RANGES)
+        .is_some_and(|x| x.as_bytes() == b"bytes");
+
+    if let (false, true, Some(total_size), Some(ranges)) = (
+        resumed,
+        supports_ranges,
+        total_size,
+        options.parallel_ranges,
+    ) {
+        if ranges > 1 && total_size >= MIN_PARALLEL_RANGES_SIZE {
+            let headers = res.headers().clone();
+            drop(res);
+
+            download_ranges(client, url, options, part_path, total_size, ranges).await?;
+
+            return Ok(headers);
+        }
+    }
+
+    let mut out_file = if resumed {
+        OpenOptions::new().append(true).open(part_path).await
+    } else {
+        File::create(part_path).await
+    }
+    .map_err(|e| format!("Failed to open part file: {:?}", e))?;
+
+    while let Some(chunk) = res
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to get chunk: {:?}", e))?
+    {
+        out_file
+            .write_all(&chunk)
+            .await
+            .map_err(|e| format!("Failed to write chunk: {:?}", e))?;
+    }
+
+    out_file
+        .flush()
+        .await
+        .map_err(|e| format!("Failed to write part file: {:?}", e))?;
+
+    if let Some(total_size) = total_size {
+        let size = part_file_len(part_path).await;
+
+        if size != total_size {
+            return Err(format!(
+                "Downloaded size ({size} bytes) does not match the expected size ({total_size} \
+                 bytes)"
+            ));
+        }
+    }
+
+    Ok(res.headers().clone())
+}
+
+/// Splits the file into `ranges` parts which are downloaded at the same time.
+///
+/// The part file is removed if any of them fails since it would have holes in it.
+async fn download_ranges(
+    client: &RequestClient,
+    url: &UrlWithMeta,
+    options: &GenericDownloaderOptions,
+    part_path: &Path,
+    total_size: u64,
+    ranges: usize,
+) -> Result<(), String> {
+    debug!(?ranges, ?total_size, "Downloading in parallel ranges");
+
+    let file = File::create(part_path)
+        .await
+        .map_err(|e| format!("Failed to create part file: {:?}", e))?;
+    file.set_len(total_size)
+        .await
+        .map_err(|e| format!("Failed to allocate part file: {:?}", e))?;
+    drop(file);
+
+    let range_size = total_size.div_ceil(ranges as u64);
+
+    let downloads = (0..total_size)
+        .step_by(usize::try_from(range_size).unwrap_or(usize::MAX))
+        .map(|start| {
+            let end = (start + range_size).min(total_size) - 1;
+
+            download_range(client, url, options, part_path, (start, end))
+        });
+
+    let res = futures::future::try_join_all(downloads).await;
+
+    if res.is_err() {
+        let _ = fs::remove_file(part_path).await;
+    }
+
+    res.map(|_| ())
+}
+
+async fn download_range(
+    client: &RequestClient,
+    url: &UrlWithMeta,
+    options: &GenericDownloaderOptions,
+    part_path: &Path,
+    (start, end): (u64, u64),
+) -> Result<(), String> {
+    let mut res = send_request(client, url, options, Some((start, Some(end))))
+        .await
+        .map_err(|e| format!("Failed to send range request: {:?}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Failed to get range response: {:?}", e))?;
+
+    if res.status() != StatusCode::PARTIAL_CONTENT {
+        return Err(format!(
+            "Server did not return the requested range: {:?}",
+            res.status()
+        ));
+    }
+
+    let mut out_file = OpenOptions::new()
+        .write(true)
+        .open(part_path)
+        .await
+        .map_err(|e| format!("Failed to open part file: {:?}", e))?;
+    out_file
+        .seek(SeekFrom::Start(start))
+        .await
+        .map_err(|e| format!("Failed to seek part file: {:?}", e))?;
+
+    let mut written = 0;
+    while let Some(chunk) = res
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to get chunk: {:?}", e))?
+    {
+        written += chunk.len() as u64;
+        out_file
+            .write_all(&chunk)
+            .await
+            .map_err(|e| format!("Failed to write chunk: {:?}", e))?;
+    }
+
+    out_file
+        .flush()
+        .await
+        .map_err(|e| format!("Failed to write part file: {:?}", e))?;
+
+    if written != end - start + 1 {
+        return Err(format!(
+            "Range {start}-{end} has {written} bytes instead of {}",
+            end - start + 1
+        ));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn url_to_filename(url: &Url, taken_filename_len: usize) -> Option<String> {
     Some(url).map(|x| PathBuf::from(x.path())).and_then(|x| {
         let stem = x.file_stem()?;
 
@@ -1,5 +1,6 @@
 pub mod generic;
 pub mod music;
+pub mod segmented;
 pub mod yt_dlp;
 
 use std::sync::{Arc, LazyLock};
@@ -19,6 +20,7 @@ pub static AVAILABLE_DOWNLOADERS: LazyLock<Vec<DownloaderEntry>> =
 fn all_downloaders() -> Vec<DownloaderEntry> {
     vec![
         Arc::new(yt_dlp::YtDlp),
+        Arc::new(segmented::Segmented),
         Arc::new(generic::Generic),
         Arc::new(music::Music),
     ]
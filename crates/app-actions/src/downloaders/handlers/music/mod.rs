@@ -48,10 +48,12 @@ impl Downloader for Music {
 
             match handler.download(req.download_dir(), song_url).await {
                 Ok(path) => {
-                    return Ok(DownloadResult {
+                    return Ok(vec![DownloadResult {
                         path,
                         request: req.clone(),
-                    })
+                        media_info: None,
+                        downloader: Some(self.name().to_string()),
+                    }])
                 }
                 Err(e) => {
                     warn!(?e, "Failed to download song");
@@ -13,7 +13,7 @@ use url::Url;
 use super::Handler;
 use crate::{
     common::request::Client,
-    downloaders::{handlers::generic::Generic, DownloadRequest, Downloader},
+    downloaders::{handlers::generic::Generic, DownloadRequest},
 };
 
 const URL_BASE: &str = "https://spotifydown.com";
@@ -49,7 +49,7 @@ impl Handler for SpotifydownProvider {
         debug!(?download_url, "Download URL found. Downloading song.");
 
         Generic
-            .download(&DownloadRequest::from_url(&download_url, download_dir))
+            .download_one(&DownloadRequest::from_url(&download_url, download_dir))
             .await
             .map(|x| x.path)
             .map_err(|e| anyhow::anyhow!(e).context("Failed to download song"))
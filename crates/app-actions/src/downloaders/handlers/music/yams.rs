@@ -13,7 +13,7 @@ use zip::ZipArchive;
 use super::Handler;
 use crate::{
     common::request::Client,
-    downloaders::{handlers::generic::Generic, DownloadRequest, Downloader},
+    downloaders::{handlers::generic::Generic, DownloadRequest},
 };
 
 const API_URL: &str = "https://yams.tf/api";
@@ -74,7 +74,7 @@ impl Handler for YamsProvider {
         );
 
         let song_zip_path = Generic
-            .download(&DownloadRequest::from_url(&download_url, download_dir))
+            .download_one(&DownloadRequest::from_url(&download_url, download_dir))
             .await
             .map_err(|e| anyhow::anyhow!(e))?;
 
@@ -0,0 +1,442 @@
+use std::fmt::Write;
+
+use tl::{HTMLTag, Node, Parser};
+use tracing::{debug, trace};
+use url::Url;
+
+use super::{Segment, SegmentFetcher, Track};
+
+/// The manifest decides how many segments there are,
+/// so don't let it make a track bigger than any sane video would be
+const MAX_SEGMENTS: u64 = 100_000;
+
+/// Returns the best video track followed by the best audio track.
+///
+/// If the adaptation sets can't be told apart,
+/// only the representation with the highest bandwidth is returned.
+pub(super) async fn get_tracks(fetcher: &SegmentFetcher, url: &Url) -> Result<Vec<Track>, String> {
+    let (url, text) = fetcher.get_text(url).await?;
+
+    let dom = tl::parse(&text, tl::ParserOptions::default())
+        .map_err(|e| format!("Failed to parse manifest: {e:?}"))?;
+    let parser = dom.parser();
+
+    let mpd = dom
+        .nodes()
+        .iter()
+        .filter_map(Node::as_tag)
+        .find(|x| x.name() == "MPD")
+        .ok_or_else(|| "Not a valid DASH manifest".to_string())?;
+
+    if attr(mpd, "type").as_deref() == Some("dynamic") {
+        return Err("Live DASH streams are not supported".to_string());
+    }
+
+    let total_duration = attr(mpd, "mediaPresentationDuration")
+        .as_deref()
+        .and_then(parse_duration);
+
+    let period =
+        child(mpd, parser, "Period").ok_or_else(|| "No period found in manifest".to_string())?;
+
+    let mpd_base = base_url(mpd, parser, &url);
+    let period_base = base_url(period, parser, &mpd_base);
+    let period_template = SegmentTemplate::from_parent(period, parser);
+
+    let mut video: Option<(u64, &HTMLTag, &HTMLTag, Url, SegmentTemplate)> = None;
+    let mut audio = None;
+    let mut other = None;
+
+    for set in children(period, parser, "AdaptationSet") {
+        let kind = content_kind(set);
+        let set_base = base_url(set, parser, &period_base);
+        let set_template = SegmentTemplate::from_parent(set, parser).inherit(&period_template);
+
+        for rep in children(set, parser, "Representation") {
+            let bandwidth = attr(rep, "bandwidth")
+                .and_then(|x| x.parse::<u64>().ok())
+                .unwrap_or_default();
+
+            let best = match kind.or_else(|| content_kind(rep)) {
+                Some(ContentKind::Video) => &mut video,
+                Some(ContentKind::Audio) => &mut audio,
+                None => &mut other,
+            };
+
+            if best
+                .as_ref()
+                .is_some_and(|(best_bandwidth, ..)| *best_bandwidth >= bandwidth)
+            {
+                continue;
+            }
+
+            *best = Some((bandwidth, rep, set, set_base.clone(), set_template.clone()));
+        }
+    }
+
+    let picked = if video.is_some() || audio.is_some() {
+        video.into_iter().chain(audio).collect::<Vec<_>>()
+    } else {
+        other.into_iter().collect()
+    };
+
+    picked
+        .into_iter()
+        .map(|(bandwidth, rep, set, set_base, set_template)| {
+            debug!(id = ?attr(rep, "id"), ?bandwidth, "Picked representation");
+
+            let rep_base = base_url(rep, parser, &set_base);
+            let template = SegmentTemplate::from_parent(rep, parser).inherit(&set_template);
+            let segment_list =
+                child(rep, parser, "SegmentList").or_else(|| child(set, parser, "SegmentList"));
+
+            let track = if let Some(list) = segment_list {
+                segment_list_track(list, parser, &rep_base)
+            } else if template.media.is_some() {
+                template.track(rep, &rep_base, total_duration)
+            } else {
+                Ok(Track {
+                    init: None,
+                    segments: vec![Segment::new(rep_base)],
+                })
+            }?;
+            trace!(?track, "Got track");
+
+            Ok(track)
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContentKind {
+    Video,
+    Audio,
+}
+
+fn content_kind(tag: &HTMLTag) -> Option<ContentKind> {
+    let kind = attr(tag, "contentType").or_else(|| {
+        attr(tag, "mimeType").and_then(|x| x.split_once('/').map(|(kind, _)| kind.to_string()))
+    })?;
+
+    match kind.as_str() {
+        "video" => Some(ContentKind::Video),
+        "audio" => Some(ContentKind::Audio),
+        _ => None,
+    }
+}
+
+/// Attributes of a `SegmentTemplate`, which are inherited from the parent elements
+#[derive(Debug, Clone, Default)]
+struct SegmentTemplate {
+    media: Option<String>,
+    initialization: Option<String>,
+    start_number: Option<u64>,
+    timescale: Option<u64>,
+    duration: Option<u64>,
+    /// `(t, d, r)` entries of the `SegmentTimeline`
+    timeline: Option<Vec<(Option<u64>, u64, i64)>>,
+}
+impl SegmentTemplate {
+    fn from_parent(parent: &HTMLTag, parser: &Parser) -> Self {
+        let Some(tag) = child(parent, parser, "SegmentTemplate") else {
+            return Self::default();
+        };
+        let num = |name: &str| attr(tag, name).and_then(|x| x.parse::<u64>().ok());
+
+        let timeline = child(tag, parser, "SegmentTimeline").map(|timeline| {
+            children(timeline, parser, "S")
+                .map(|s| {
+                    (
+                        attr(s, "t").and_then(|x| x.parse().ok()),
+                        attr(s, "d")
+                            .and_then(|x| x.parse().ok())
+                            .unwrap_or_default(),
+                        attr(s, "r")
+                            .and_then(|x| x.parse().ok())
+                            .unwrap_or_default(),
+                    )
+                })
+                .collect()
+        });
+
+        Self {
+            media: attr(tag, "media"),
+            initialization: attr(tag, "initialization"),
+            start_number: num("startNumber"),
+            timescale: num("timescale"),
+            duration: num("duration"),
+            timeline,
+        }
+    }
+
+    fn inherit(self, parent: &Self) -> Self {
+        Self {
+            media: self.media.or_else(|| parent.media.clone()),
+            initialization: self
+                .initialization
+                .or_else(|| parent.initialization.clone()),
+            start_number: self.start_number.or(parent.start_number),
+            timescale: self.timescale.or(parent.timescale),
+            duration: self.duration.or(parent.duration),
+            timeline: self.timeline.or_else(|| parent.timeline.clone()),
+        }
+    }
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn track(
+        &self,
+        rep: &HTMLTag,
+        base: &Url,
+        total_duration: Option<f64>,
+    ) -> Result<Track, String> {
+        let media = self.media.as_deref().unwrap_or_default();
+        let rep_id = attr(rep, "id").unwrap_or_default();
+        let bandwidth = attr(rep, "bandwidth").unwrap_or_default();
+        let timescale = self.timescale.unwrap_or(1);
+        let total_duration = total_duration.map(|x| x * timescale as f64);
+
+        let fill = |template: &str, number: u64, time: u64| {
+            let path = fill_template(template, &rep_id, &bandwidth, number, time);
+
+            base.join(&path)
+                .map_err(|e| format!("Invalid segment URL {path:?}: {e:?}"))
+        };
+
+        let init = self
+            .initialization
+            .as_deref()
+            .map(|x| fill(x, 0, 0).map(Segment::new))
+            .transpose()?;
+
+        // (number, time) of each segment
+        let mut numbers = vec![];
+        let mut number = self.start_number.unwrap_or(1);
+
+        if let Some(timeline) = &self.timeline {
+            let mut time = 0;
+
+            for (i, (t, d, r)) in timeline.iter().enumerate() {
+                if let Some(t) = t {
+                    time = *t;
+                }
+
+                if *d == 0 {
+                    continue;
+                }
+
+                // A negative repeat count fills up until the next entry or the end
+                let repeat = if *r < 0 {
+                    let end = timeline
+                        .get(i + 1)
+                        .and_then(|(t, ..)| *t)
+                        .map(|x| x as f64)
+                        .or(total_duration)
+                        .ok_or_else(|| "Unknown length of open-ended timeline".to_string())?;
+
+                    ((end - time as f64) / *d as f64).ceil().max(1.0) as u64 - 1
+                } else {
+                    r.unsigned_abs()
+                };
+
+                if (numbers.len() as u64)
+                    .saturating_add(repeat)
+                    .saturating_add(1)
+                    > MAX_SEGMENTS
+                {
+                    return Err(too_many_segments());
+                }
+
+                for _ in 0..=repeat {
+                    numbers.push((number, time));
+                    number = number.checked_add(1).ok_or_else(segment_overflow)?;
+                    time = time.checked_add(*d).ok_or_else(segment_overflow)?;
+                }
+            }
+        } else {
+            let duration = self
+                .duration
+                .filter(|x| *x > 0)
+                .ok_or_else(|| "Segment template has no duration".to_string())?;
+            let total_duration = total_duration
+                .ok_or_else(|| "Manifest has no presentation duration".to_string())?;
+
+            let count = (total_duration / duration as f64).ceil() as u64;
+            if count > MAX_SEGMENTS {
+                return Err(too_many_segments());
+            }
+
+            for i in 0..count {
+                numbers.push((
+                    number.checked_add(i).ok_or_else(segment_overflow)?,
+                    i.checked_mul(duration).ok_or_else(segment_overflow)?,
+                ));
+            }
+        }
+
+        let segments = numbers
+            .into_iter()
+            .map(|(number, time)| fill(media, number, time).map(Segment::new))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Track { init, segments })
+    }
+}
+
+fn too_many_segments() -> String {
+    format!("Manifest has more than {MAX_SEGMENTS} segments")
+}
+
+fn segment_overflow() -> String {
+    "Segment number or time in manifest is too large".to_string()
+}
+
+fn segment_list_track(list: &HTMLTag, parser: &Parser, base: &Url) -> Result<Track, String> {
+    let segment = |tag: &HTMLTag, url_attr: &str, range_attr: &str| {
+        let url = match attr(tag, url_attr) {
+            Some(x) => base
+                .join(&x)
+                .map_err(|e| format!("Invalid segment URL {x:?}: {e:?}"))?,
+            None => base.clone(),
+        };
+        let range = attr(tag, range_attr).as_deref().and_then(parse_range);
+
+        Ok::<_, String>(Segment::new(url).with_range(range))
+    };
+
+    let init = child(list, parser, "Initialization")
+        .map(|x| segment(x, "sourceURL", "range"))
+        .transpose()?;
+
+    let segments = children(list, parser, "SegmentURL")
+        .map(|x| segment(x, "media", "mediaRange"))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Track { init, segments })
+}
+
+/// Replaces the `$Identifier$` placeholders of a segment template
+fn fill_template(template: &str, rep_id: &str, bandwidth: &str, number: u64, time: u64) -> String {
+    let mut res = String::with_capacity(template.len());
+    let mut parts = template.split('$');
+
+    if let Some(first) = parts.next() {
+        res.push_str(first);
+    }
+
+    // Placeholders are every other part
+    while let Some(placeholder) = parts.next() {
+        let (name, format) = placeholder
+            .split_once('%')
+            .map_or((placeholder, None), |(name, format)| (name, Some(format)));
+        let width = format
+            .and_then(|x| x.strip_prefix('0'))
+            .and_then(|x| x.strip_suffix('d'))
+            .and_then(|x| x.parse::<usize>().ok())
+            .unwrap_or_default();
+
+        match name {
+            "" => res.push('$'),
+            "RepresentationID" => res.push_str(rep_id),
+            "Bandwidth" => {
+                let _ = write!(res, "{bandwidth:0>width$}");
+            }
+            "Number" => {
+                let _ = write!(res, "{number:0width$}");
+            }
+            "Time" => {
+                let _ = write!(res, "{time:0width$}");
+            }
+            _ => {
+                res.push('$');
+                res.push_str(placeholder);
+                res.push('$');
+            }
+        }
+
+        if let Some(literal) = parts.next() {
+            res.push_str(literal);
+        }
+    }
+
+    res
+}
+
+/// Parses an ISO-8601 duration (eg. `PT1H2M3.5S`) into seconds
+fn parse_duration(value: &str) -> Option<f64> {
+    let value = value.trim().strip_prefix('P')?;
+    let mut seconds = 0.0;
+    let mut in_time = false;
+    let mut number = String::new();
+
+    for c in value.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' | '.' => number.push(c),
+            _ => {
+                let n = number.parse::<f64>().ok()?;
+                number.clear();
+
+                seconds += n * match (c, in_time) {
+                    ('D', false) => 86400.0,
+                    ('H', true) => 3600.0,
+                    ('M', true) => 60.0,
+                    ('S', true) => 1.0,
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    Some(seconds)
+}
+
+/// Parses `<start>-<end>`
+fn parse_range(value: &str) -> Option<(u64, u64)> {
+    let (start, end) = value.split_once('-')?;
+
+    Some((start.trim().parse().ok()?, end.trim().parse().ok()?))
+}
+
+fn attr(tag: &HTMLTag, name: &str) -> Option<String> {
+    tag.attributes()
+        .get(name)
+        .flatten()
+        .map(|x| x.as_utf8_str().trim().to_string())
+}
+
+fn children<'p, 'a: 'p>(
+    tag: &HTMLTag<'a>,
+    parser: &'p Parser<'a>,
+    name: &'p str,
+) -> impl Iterator<Item = &'p HTMLTag<'a>> {
+    tag.children()
+        .top()
+        .iter()
+        .copied()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .filter_map(|x| x.get(parser))
+        .filter_map(Node::as_tag)
+        .filter(move |x| x.name() == name)
+}
+
+fn child<'p, 'a: 'p>(
+    tag: &HTMLTag<'a>,
+    parser: &'p Parser<'a>,
+    name: &'p str,
+) -> Option<&'p HTMLTag<'a>> {
+    children(tag, parser, name).next()
+}
+
+/// Resolves the `BaseURL` of the element against the one of its parent
+fn base_url(tag: &HTMLTag, parser: &Parser, parent: &Url) -> Url {
+    child(tag, parser, "BaseURL")
+        .map(|x| x.inner_text(parser).trim().to_string())
+        .filter(|x| !x.is_empty())
+        .and_then(|x| parent.join(&x).ok())
+        .unwrap_or_else(|| parent.clone())
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use tracing::{debug, trace};
+use url::Url;
+
+use super::{Segment, SegmentFetcher, Track};
+
+/// Returns the main track of the best variant
+/// followed by its separate audio track if it has one.
+pub(super) async fn get_tracks(fetcher: &SegmentFetcher, url: &Url) -> Result<Vec<Track>, String> {
+    let (url, text) = fetcher.get_text(url).await?;
+
+    let playlist = match Playlist::parse(&url, &text)? {
+        Playlist::Media(track) => return Ok(vec![track]),
+        Playlist::Master(x) => x,
+    };
+    trace!(?playlist, "Got master playlist");
+
+    let variant = playlist
+        .variants
+        .iter()
+        .max_by_key(|x| x.bandwidth)
+        .ok_or_else(|| "No variants found in master playlist".to_string())?;
+    debug!(?variant, "Picked variant");
+
+    let mut tracks = vec![get_media_track(fetcher, &variant.url).await?];
+
+    let audio = variant.audio_group.as_ref().and_then(|group| {
+        let renditions = playlist
+            .audio
+            .iter()
+            .filter(|x| x.group_id == *group)
+            .collect::<Vec<_>>();
+
+        renditions
+            .iter()
+            .find(|x| x.is_default)
+            .or_else(|| renditions.first())
+            .and_then(|x| x.url.clone())
+    });
+
+    // Renditions without a URI are already muxed into the variant
+    if let Some(audio_url) = audio {
+        debug!(url = ?audio_url.as_str(), "Picked audio rendition");
+        tracks.push(get_media_track(fetcher, &audio_url).await?);
+    }
+
+    Ok(tracks)
+}
+
+async fn get_media_track(fetcher: &SegmentFetcher, url: &Url) -> Result<Track, String> {
+    let (url, text) = fetcher.get_text(url).await?;
+
+    match Playlist::parse(&url, &text)? {
+        Playlist::Media(track) => Ok(track),
+        Playlist::Master(_) => {
+            Err("Expected a media playlist but got a master playlist".to_string())
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Playlist {
+    Master(MasterPlaylist),
+    Media(Track),
+}
+
+#[derive(Debug, Default)]
+struct MasterPlaylist {
+    variants: Vec<Variant>,
+    audio: Vec<AudioRendition>,
+}
+
+#[derive(Debug)]
+struct Variant {
+    url: Url,
+    bandwidth: u64,
+    audio_group: Option<String>,
+}
+
+#[derive(Debug)]
+struct AudioRendition {
+    group_id: String,
+    url: Option<Url>,
+    is_default: bool,
+}
+
+impl Playlist {
+    fn parse(base_url: &Url, text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().map(str::trim).filter(|x| !x.is_empty());
+
+        if lines.next() != Some("#EXTM3U") {
+            return Err("Not a valid HLS playlist".to_string());
+        }
+
+        if lines.clone().any(|x| x.starts_with("#EXT-X-STREAM-INF")) {
+            Ok(Self::Master(parse_master(base_url, lines)))
+        } else {
+            parse_media(base_url, lines).map(Self::Media)
+        }
+    }
+}
+
+fn parse_master<'a>(base_url: &Url, lines: impl Iterator<Item = &'a str>) -> MasterPlaylist {
+    let mut playlist = MasterPlaylist::default();
+    let mut pending_variant = None;
+
+    for line in lines {
+        if let Some(attrs) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            pending_variant = Some(parse_attributes(attrs));
+        } else if let Some(attrs) = line.strip_prefix("#EXT-X-MEDIA:") {
+            let attrs = parse_attributes(attrs);
+
+            if attrs.get("TYPE").map(String::as_str) != Some("AUDIO") {
+                continue;
+            }
+
+            playlist.audio.push(AudioRendition {
+                group_id: attrs.get("GROUP-ID").cloned().unwrap_or_default(),
+                url: attrs.get("URI").and_then(|x| base_url.join(x).ok()),
+                is_default: attrs.get("DEFAULT").map(String::as_str) == Some("YES"),
+            });
+        } else if !line.starts_with('#') {
+            let Some(attrs) = pending_variant.take() else {
+                continue;
+            };
+            let Ok(url) = base_url.join(line) else {
+                continue;
+            };
+
+            playlist.variants.push(Variant {
+                url,
+                bandwidth: attrs
+                    .get("BANDWIDTH")
+                    .and_then(|x| x.parse().ok())
+                    .unwrap_or_default(),
+                audio_group: attrs.get("AUDIO").cloned(),
+            });
+        }
+    }
+
+    playlist
+}
+
+fn parse_media<'a>(base_url: &Url, lines: impl Iterator<Item = &'a str>) -> Result<Track, String> {
+    let mut track = Track::default();
+    // `#EXT-X-BYTERANGE` offsets default to the end of the previous range
+    let mut next_offset = 0;
+    let mut pending_range = None;
+
+    for line in lines {
+        if let Some(attrs) = line.strip_prefix("#EXT-X-KEY:") {
+            let attrs = parse_attributes(attrs);
+
+            if attrs.get("METHOD").is_some_and(|x| x != "NONE") {
+                return Err("Encrypted HLS streams are not supported".to_string());
+            }
+        } else if let Some(attrs) = line.strip_prefix("#EXT-X-MAP:") {
+            let attrs = parse_attributes(attrs);
+
+            let url = attrs
+                .get("URI")
+                .and_then(|x| base_url.join(x).ok())
+                .ok_or_else(|| "Invalid init segment URI".to_string())?;
+            let range = attrs.get("BYTERANGE").and_then(|x| parse_byte_range(x, 0));
+
+            track.init = Some(Segment::new(url).with_range(range));
+        } else if let Some(range) = line.strip_prefix("#EXT-X-BYTERANGE:") {
+            pending_range = parse_byte_range(range, next_offset);
+            next_offset = pending_range
+                .and_then(|(_, end)| end.checked_add(1))
+                .unwrap_or(next_offset);
+        } else if !line.starts_with('#') {
+            let url = base_url
+                .join(line)
+                .map_err(|e| format!("Invalid segment URI {line:?}: {e:?}"))?;
+
+            track
+                .segments
+                .push(Segment::new(url).with_range(pending_range.take()));
+        }
+    }
+
+    Ok(track)
+}
+
+/// Parses `<length>[@<offset>]` into an inclusive range
+fn parse_byte_range(value: &str, default_offset: u64) -> Option<(u64, u64)> {
+    let (length, offset) = match value.split_once('@') {
+        Some((length, offset)) => (length.parse::<u64>().ok()?, offset.parse::<u64>().ok()?),
+        None => (value.parse::<u64>().ok()?, default_offset),
+    };
+
+    if length == 0 {
+        return None;
+    }
+
+    Some((offset, offset.checked_add(length - 1)?))
+}
+
+/// Parses an attribute list (eg. `BANDWIDTH=1280000,CODECS="avc1.4d401f,mp4a.40.2"`)
+fn parse_attributes(attrs: &str) -> HashMap<String, String> {
+    let mut res = HashMap::new();
+    let mut rest = attrs.trim();
+
+    while let Some((key, value_and_rest)) = rest.split_once('=') {
+        let key = key.trim().trim_start_matches(',').trim().to_string();
+
+        let (value, next) = value_and_rest.strip_prefix('"').map_or_else(
+            || {
+                let end = value_and_rest.find(',').unwrap_or(value_and_rest.len());
+
+                (&value_and_rest[..end], &value_and_rest[end..])
+            },
+            |quoted| {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+
+                (&quoted[..end], quoted.get(end + 1..).unwrap_or_default())
+            },
+        );
+
+        res.insert(key, value.to_string());
+        rest = next;
+    }
+
+    res
+}
@@ -0,0 +1,290 @@
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use app_helpers::{futures::retry_fn, id::time_id, temp_dir::TempDir};
+use futures::{stream, StreamExt, TryStreamExt};
+use http::{header, HeaderMap};
+use serde::{Deserialize, Serialize};
+use tokio::{fs::File, io::AsyncWriteExt};
+use tokio_util::bytes::Bytes;
+use tracing::{debug, info, trace};
+use url::Url;
+
+use super::{
+    generic::url_to_filename, DownloadRequest, DownloadResult, Downloader, DownloaderReturn,
+};
+use crate::{
+    common::request::{Client, RequestClient},
+    config::ActionsConfig,
+};
+
+mod dash;
+mod hls;
+
+const MAX_CONCURRENT_SEGMENTS: usize = 8;
+const MAX_SEGMENT_RETRIES: u32 = 5;
+
+/// Downloads HLS (`.m3u8`) and DASH (`.mpd`) streams without yt-dlp.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Segmented;
+
+#[async_trait::async_trait]
+#[typetag::serde]
+impl Downloader for Segmented {
+    fn description(&self) -> &'static str {
+        "Downloads HLS and DASH streams by fetching the segments of the best variant and remuxing \
+         them with ffmpeg."
+    }
+
+    async fn can_download(&self, req: &DownloadRequest) -> bool {
+        Self::is_manifest_url(req.url.url())
+    }
+
+    async fn download(&self, request: &DownloadRequest) -> DownloaderReturn {
+        self.download_one(request).await.map(|x| vec![x])
+    }
+}
+
+impl Segmented {
+    #[must_use]
+    pub fn is_manifest_url(url: &Url) -> bool {
+        if !matches!(url.scheme(), "http" | "https") {
+            return false;
+        }
+
+        ManifestKind::from_url(url).is_some()
+    }
+
+    pub async fn download_one(&self, request: &DownloadRequest) -> Result<DownloadResult, String> {
+        let url = request.url.url();
+
+        info!(url = ?url.as_str(), dir = ?request.download_dir(), "Downloading segmented stream");
+
+        let kind = ManifestKind::from_url(url)
+            .ok_or_else(|| format!("URL is not a HLS or DASH manifest: {:?}", url.as_str()))?;
+
+        let fetcher = SegmentFetcher::new(request.url.headers().clone())?;
+
+        let tracks = match kind {
+            ManifestKind::Hls => hls::get_tracks(&fetcher, url).await?,
+            ManifestKind::Dash => dash::get_tracks(&fetcher, url).await?,
+        };
+        debug!(count = tracks.len(), "Got tracks from manifest");
+        trace!(?tracks, "Tracks");
+
+        if tracks.is_empty() {
+            return Err("No playable tracks found in manifest".to_string());
+        }
+
+        let temp_dir = TempDir::in_tmp_with_prefix("downloader-hub_segmented-")
+            .map_err(|e| format!("Failed to create temporary directory: {e:?}"))?;
+
+        let mut track_paths = vec![];
+        for (i, track) in tracks.iter().enumerate() {
+            let track_path = temp_dir.path().join(format!("track-{i}"));
+
+            fetcher.download_track(track, &track_path).await?;
+
+            track_paths.push(track_path);
+        }
+
+        let file_path = output_file_path(request.download_dir(), url);
+
+        remux(&track_paths, &file_path).await?;
+
+        Ok(DownloadResult {
+            request: request.clone(),
+            path: file_path,
+            media_info: None,
+            downloader: Some(self.name().to_string()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ManifestKind {
+    Hls,
+    Dash,
+}
+impl ManifestKind {
+    fn from_url(url: &Url) -> Option<Self> {
+        let extension = Path::new(url.path()).extension()?;
+
+        if extension.eq_ignore_ascii_case("m3u8") {
+            Some(Self::Hls)
+        } else if extension.eq_ignore_ascii_case("mpd") {
+            Some(Self::Dash)
+        } else {
+            None
+        }
+    }
+}
+
+/// A single media track (eg. video or audio) split into segments.
+#[derive(Debug, Clone, Default)]
+struct Track {
+    init: Option<Segment>,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    url: Url,
+    /// Inclusive byte range of the segment in the file
+    range: Option<(u64, u64)>,
+}
+impl Segment {
+    const fn new(url: Url) -> Self {
+        Self { url, range: None }
+    }
+
+    const fn with_range(mut self, range: Option<(u64, u64)>) -> Self {
+        self.range = range;
+        self
+    }
+}
+
+/// Fetches manifests and segments with the headers of the original request.
+struct SegmentFetcher {
+    client: RequestClient,
+    headers: HeaderMap,
+}
+impl SegmentFetcher {
+    fn new(headers: HeaderMap) -> Result<Self, String> {
+        Ok(Self {
+            client: Client::base()?,
+            headers,
+        })
+    }
+
+    /// Returns the final URL (after redirects) along with the body
+    async fn get_text(&self, url: &Url) -> Result<(Url, String), String> {
+        let resp = self
+            .client
+            .get(url.as_str())
+            .headers(ActionsConfig::credentials().apply(url, &self.headers))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send manifest request: {e:?}"))?
+            .error_for_status()
+            .map_err(|e| format!("Failed to get manifest: {e:?}"))?;
+
+        let final_url = resp.url().clone();
+        let text = resp
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read manifest: {e:?}"))?;
+
+        Ok((final_url, text))
+    }
+
+    async fn get_segment(&self, segment: &Segment) -> Result<Bytes, String> {
+        retry_fn(MAX_SEGMENT_RETRIES, || async {
+            let mut req = self
+                .client
+                .get(segment.url.as_str())
+                .headers(ActionsConfig::credentials().apply(&segment.url, &self.headers));
+
+            if let Some((start, end)) = segment.range {
+                req = req.header(header::RANGE, format!("bytes={start}-{end}"));
+            }
+
+            req.send()
+                .await
+                .map_err(|e| format!("Failed to send segment request: {e:?}"))?
+                .error_for_status()
+                .map_err(|e| format!("Failed to get segment: {e:?}"))?
+                .bytes()
+                .await
+                .map_err(|e| format!("Failed to read segment: {e:?}"))
+        })
+        .await
+        .map_err(|e| format!("Failed to download segment {:?}: {e}", segment.url.as_str()))
+    }
+
+    /// Segments are downloaded concurrently but written to the file in order.
+    async fn download_track(&self, track: &Track, path: &Path) -> Result<(), String> {
+        debug!(?path, segments = track.segments.len(), "Downloading track");
+
+        let mut file = File::create(path)
+            .await
+            .map_err(|e| format!("Failed to create track file: {e:?}"))?;
+
+        let segments = track
+            .init
+            .iter()
+            .chain(&track.segments)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut segments = stream::iter(segments)
+            .map(|x| async move { self.get_segment(&x).await })
+            .buffered(MAX_CONCURRENT_SEGMENTS)
+            .into_stream();
+
+        while let Some(data) = segments.try_next().await? {
+            file.write_all(&data)
+                .await
+                .map_err(|e| format!("Failed to write segment: {e:?}"))?;
+        }
+
+        file.flush()
+            .await
+            .map_err(|e| format!("Failed to write track file: {e:?}"))?;
+
+        Ok(())
+    }
+}
+
+fn output_file_path(download_dir: &Path, url: &Url) -> PathBuf {
+    let id = time_id();
+    let extension = "mp4";
+    let taken_filename_len = id.len() + 1 + extension.len();
+
+    let mut file_name = OsString::from(&id);
+    file_name.push(".");
+    file_name
+        .push(url_to_filename(url, taken_filename_len).unwrap_or_else(|| "stream".to_string()));
+    file_name.push(".");
+    file_name.push(extension);
+
+    download_dir.join(file_name)
+}
+
+/// The first track is the main one (video or muxed), the second one is a separate audio track.
+async fn remux(track_paths: &[PathBuf], output_path: &Path) -> Result<(), String> {
+    let mut cmd = tokio::process::Command::new(ActionsConfig::dependency_paths().ffmpeg_path());
+    cmd.kill_on_drop(true);
+    cmd.arg("-y");
+
+    for path in track_paths {
+        cmd.arg("-i").arg(path);
+    }
+
+    if track_paths.len() > 1 {
+        cmd.args(["-map", "0:v", "-map", "1:a"]);
+    } else {
+        cmd.args(["-map", "0"]);
+    }
+
+    cmd.args(["-c", "copy"])
+        .args(["-movflags", "+faststart"])
+        .arg(output_path);
+
+    trace!("Running command: {cmd:?}");
+
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ffmpeg: {e:?}"))?;
+
+    trace!("Command output: {output:?}");
+
+    if !output.status.success() {
+        return Err(format!("ffmpeg exited with error code {output:?}"));
+    }
+
+    Ok(())
+}
@@ -1,19 +1,27 @@
 use std::{
     io::Write,
     ops::Sub,
-    path::PathBuf,
+    path::{Path, PathBuf},
     process,
     time::{Duration, SystemTime},
 };
 
 use app_helpers::{id::time_id, temp_dir::TempDir, temp_file::TempFile};
+use chrono::NaiveDate;
 use http::header;
 use serde::{Deserialize, Serialize};
 use tokio::process::Command;
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
+use url::Url;
 
 use super::{generic, DownloadRequest, DownloadResult, Downloader, DownloaderReturn};
-use crate::{common::request::USER_AGENT, config::ActionsConfig};
+use crate::{
+    common::request::USER_AGENT,
+    config::ActionsConfig,
+    downloaders::{DownloaderOptions, MediaChapter, MediaInfo},
+};
+
+const DEFAULT_MAX_PLAYLIST_ITEMS: usize = 25;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct YtDlp;
@@ -30,13 +38,143 @@ impl Downloader for YtDlp {
     }
 
     async fn download(&self, req: &DownloadRequest) -> DownloaderReturn {
-        self.download_one(req).await
+        let options = req
+            .downloader_options::<YtDlpDownloaderOptions>()
+            .unwrap_or_default();
+
+        trace!(?options, "Using downloader options");
+
+        if options.playlist {
+            self.download_playlist(req, options.max_playlist_items())
+                .await
+        } else {
+            self.download_one(req).await.map(|x| vec![x])
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct YtDlpDownloaderOptions {
+    /// Download every entry if the URL points to a playlist, set or multi-video post
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    playlist: bool,
+    /// The maximum number of playlist entries to download.
+    /// Defaults to [`DEFAULT_MAX_PLAYLIST_ITEMS`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_playlist_items: Option<usize>,
+    /// Which formats yt-dlp should prefer. Defaults to [`QualityPreset::Best`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quality: Option<QualityPreset>,
+}
+impl YtDlpDownloaderOptions {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub const fn with_playlist(mut self, playlist: bool) -> Self {
+        self.playlist = playlist;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_max_playlist_items(mut self, max_playlist_items: Option<usize>) -> Self {
+        self.max_playlist_items = max_playlist_items;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_quality(mut self, quality: Option<QualityPreset>) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    fn max_playlist_items(&self) -> usize {
+        self.max_playlist_items
+            .filter(|x| *x > 0)
+            .unwrap_or(DEFAULT_MAX_PLAYLIST_ITEMS)
+    }
+}
+impl From<YtDlpDownloaderOptions> for DownloaderOptions {
+    fn from(val: YtDlpDownloaderOptions) -> Self {
+        let val = serde_json::to_value(val)
+            .ok()
+            .and_then(|x| x.as_object().cloned())
+            .expect("Failed to serialize options");
+
+        val.into_iter().collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QualityPreset {
+    /// Let yt-dlp pick the best quality available
+    #[default]
+    Best,
+    /// Prefer h264 video up to 1080p and files under 50 MB
+    /// so they can be uploaded to Telegram without re-encoding
+    TelegramFriendly,
+    /// Only download the best audio track
+    AudioOnly,
+    /// Prefer the smallest file
+    Smallest,
+}
+impl QualityPreset {
+    /// The format selector (`-f`) for the preset
+    #[must_use]
+    pub const fn format(self) -> Option<&'static str> {
+        match self {
+            Self::AudioOnly => Some("ba/b"),
+            Self::Best | Self::TelegramFriendly | Self::Smallest => None,
+        }
+    }
+
+    /// The format sort expression (`-S`) for the preset.
+    /// See <https://github.com/yt-dlp/yt-dlp#sorting-formats>
+    #[must_use]
+    pub const fn format_sort(self) -> Option<&'static str> {
+        match self {
+            Self::Best => None,
+            Self::TelegramFriendly => Some("vcodec:h264,res:1080,filesize:50M,ext:mp4:m4a"),
+            Self::AudioOnly => Some("acodec:aac,abr"),
+            Self::Smallest => Some("+size,+br,+res,+fps"),
+        }
     }
 }
 
 impl YtDlp {
-    #[allow(clippy::too_many_lines)]
+    #[must_use]
+    pub fn options() -> YtDlpDownloaderOptions {
+        YtDlpDownloaderOptions::default()
+    }
+
     pub async fn download_one(&self, request: &DownloadRequest) -> Result<DownloadResult, String> {
+        self.run(request, None)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| "yt-dlp finished but no file was downloaded.".to_string())
+    }
+
+    /// Downloads up to `max_items` entries of a playlist.
+    /// URLs that aren't playlists are downloaded as usual.
+    pub async fn download_playlist(
+        &self,
+        request: &DownloadRequest,
+        max_items: usize,
+    ) -> Result<Vec<DownloadResult>, String> {
+        self.run(request, Some(max_items)).await
+    }
+
+    #[allow(clippy::too_many_lines)]
+    async fn run(
+        &self,
+        request: &DownloadRequest,
+        playlist_items: Option<usize>,
+    ) -> Result<Vec<DownloadResult>, String> {
         let yt_dlp = ActionsConfig::dependency_paths().yt_dlp_path();
         trace!("`yt-dlp' binary: {:?}", &yt_dlp);
         let temp_dir = TempDir::in_tmp_with_prefix("downloader-hub_yt-dlp-")
@@ -52,6 +190,15 @@ impl YtDlp {
             .unwrap_or_else(|_| Duration::from_secs(0))
             .as_secs();
 
+        // yt-dlp sends `--add-header` headers to every host it requests (eg. CDNs and redirects),
+        // so only the stored cookies are passed, through the host-scoped cookie file.
+        // Headers set on the request itself are still passed as is.
+        let credentials = ActionsConfig::credentials();
+        if credentials.has_headers_for(parsed_url) {
+            debug!("Not passing stored credential headers to yt-dlp");
+        }
+        let headers = request.url.headers();
+
         let cookie_values = request
             .url
             .headers()
@@ -71,10 +218,14 @@ impl YtDlp {
                         )
                     })
             })
+            .chain(credentials.netscape_cookie_lines(parsed_url))
             .collect::<Vec<String>>();
 
         debug!("template: {:?}", &output_template);
+        // Has to live until yt-dlp exits
+        let mut cookie_file = None;
         let mut cmd = Command::new(yt_dlp);
+        cmd.kill_on_drop(true);
         let cmd = {
             let mut cmd = cmd
                 .arg("--no-check-certificate")
@@ -83,14 +234,45 @@ impl YtDlp {
                 .arg("--no-mtime")
                 .arg("--no-embed-metadata")
                 .arg("--no-config")
-                .arg("--no-playlist");
+                .arg("--write-info-json");
+
+            let quality = request
+                .downloader_options::<YtDlpDownloaderOptions>()
+                .and_then(|x| x.quality)
+                .unwrap_or_default();
+            trace!(?quality, "Using quality preset");
+
+            if let Some(format) = quality.format() {
+                cmd = cmd.args(["--format", format]);
+            }
+
+            if let Some(format_sort) = quality.format_sort() {
+                cmd = cmd.args(["--format-sort", format_sort]);
+            }
+
+            cmd = match playlist_items {
+                Some(max_items) => cmd
+                    .arg("--yes-playlist")
+                    .args(["--playlist-items", &format!("1:{max_items}")])
+                    .arg("--ignore-errors"),
+                None => cmd.arg("--no-playlist"),
+            };
+
+            let proxy = ActionsConfig::proxy();
+            if proxy.is_enabled() {
+                // An empty proxy makes yt-dlp connect directly
+                let proxy = proxy.proxy_for(host_str).map_or("", Url::as_str);
+
+                cmd = cmd.args(["--proxy", proxy]);
+            }
 
             if !cookie_values.is_empty() {
-                debug!("Adding cookie headers: {:?}", &cookie_values);
+                debug!(count = cookie_values.len(), "Adding cookies");
 
-                let mut cookie_file = TempFile::with_prefix("cookie-headers-").map_err(|e| {
-                    format!("Failed to create temporary file for yt-dlp cookie headers: {e:?}")
-                })?;
+                let cookie_file =
+                    cookie_file.insert(TempFile::with_prefix("cookie-headers-").map_err(|e| {
+                        format!("Failed to create temporary file for yt-dlp cookie headers: {e:?}")
+                    })?);
 
                 cookie_file
                     .file_mut()
@@ -112,9 +294,7 @@ impl YtDlp {
                     generic::MAX_FILENAME_LENGTH.sub(5).to_string().as_str(),
                 ])
                 .args(
-                    request
-                        .url
-                        .headers()
+                    headers
                         .iter()
                         .filter(|x| x.0 != header::COOKIE)
                         .flat_map(|(k, v)| {
@@ -137,57 +317,87 @@ impl YtDlp {
 
             cmd
         };
-        debug!("Running cmd: {:?}", &cmd);
+        debug!("Running cmd: {:?}", loggable_command(cmd));
         let cmd_output = cmd.output().await;
         trace!("Cmd output: {:?}", &cmd_output);
-        let new_file_path = match cmd_output {
-            Ok(process::Output {
-                stdout,
-                stderr: _,
-                status,
-            }) if status.success() => {
-                let output = String::from_utf8(stdout)
-                    .map_err(|e| format!("Failed to convert output to UTF-8: {e:?}"))?;
-                let output_path = PathBuf::from(output.trim());
-
-                if !output_path.exists() {
+        let output_paths = cmd_output
+            .as_ref()
+            .map(|x| output_file_paths(&String::from_utf8_lossy(&x.stdout)))
+            .unwrap_or_default();
+        let new_file_paths = match &cmd_output {
+            // With `--ignore-errors` failed playlist entries still fail the whole command,
+            // so a playlist only fails if nothing was downloaded
+            Ok(process::Output { status, .. })
+                if status.success() || (playlist_items.is_some() && !output_paths.is_empty()) =>
+            {
+                if output_paths.is_empty() {
                     return Err("yt-dlp finished but file does not exist.".to_string());
                 }
 
-                debug!("yt-dlp successful download to file: {:?}", output_path);
-                output_path
+                debug!("yt-dlp successful download to files: {:?}", output_paths);
+                output_paths
             }
             Ok(process::Output {
                 stdout: _,
                 stderr,
                 status: _,
             }) if is_image_error(stderr.clone()) => {
-                return generic::Generic.download(request).await
+                return generic::Generic
+                    .download_one(request)
+                    .await
+                    .map(|x| vec![x]);
             }
             _ => {
                 return Err(format!("yt-dlp failed downloading meme: {cmd_output:?}"));
             }
         };
 
-        if !new_file_path.exists() {
-            return Err("yt-dlp finished but file does not exist.".to_string());
-        }
+        let mut results = vec![];
+        for new_file_path in new_file_paths {
+            let final_file_path = request
+                .download_dir()
+                .join(new_file_path.file_name().unwrap_or_default());
 
-        let final_file_path = request
-            .download_dir()
-            .join(new_file_path.file_name().unwrap_or_default());
+            std::fs::copy(&new_file_path, &final_file_path).map_err(|e| {
+                format!("Failed to copy file from {new_file_path:?} to {final_file_path:?}: {e:?}")
+            })?;
 
-        std::fs::copy(&new_file_path, &final_file_path).map_err(|e| {
-            format!("Failed to copy file from {new_file_path:?} to {final_file_path:?}: {e:?}")
-        })?;
+            let media_info = match read_info_json(&new_file_path).await {
+                Ok(x) => Some(x),
+                Err(e) => {
+                    warn!(?e, "Failed to read yt-dlp info json");
+                    None
+                }
+            };
+            trace!(?media_info, "Got media info");
 
-        Ok(DownloadResult {
-            request: request.clone(),
-            path: final_file_path,
-        })
+            results.push(DownloadResult {
+                request: request.clone(),
+                path: final_file_path,
+                media_info,
+                downloader: Some(self.name().to_string()),
+            });
+        }
+
+        Ok(results)
     }
 }
 
+/// yt-dlp prints the path of every downloaded file on a separate line.
+fn output_file_paths(output: &str) -> Vec<PathBuf> {
+    let mut paths = output
+        .lines()
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .map(PathBuf::from)
+        .filter(|x| x.exists())
+        .collect::<Vec<_>>();
+
+    paths.dedup();
+
+    paths
+}
+
 fn get_output_template<S: Into<PathBuf>>(download_dir: S) -> PathBuf {
     let file_identifier = time_id();
     let file_name = format!("{file_identifier}.%(id).64s.%(ext)s");
@@ -195,6 +405,74 @@ fn get_output_template<S: Into<PathBuf>>(download_dir: S) -> PathBuf {
     download_dir.into().join(file_name)
 }
 
+/// yt-dlp writes the info json next to the downloaded file
+/// with the extension replaced by `.info.json`.
+async fn read_info_json(file_path: &Path) -> Result<MediaInfo, String> {
+    let info_json_path = file_path.with_extension("info.json");
+
+    let info_json = tokio::fs::read(&info_json_path)
+        .await
+        .map_err(|e| format!("Failed to read info json from {info_json_path:?}: {e:?}"))?;
+
+    let info_json = serde_json::from_slice::<YtDlpInfoJson>(&info_json)
+        .map_err(|e| format!("Failed to parse info json: {e:?}"))?;
+
+    Ok(info_json.into())
+}
+
+/// The subset of the yt-dlp info json that is kept.
+/// See <https://github.com/yt-dlp/yt-dlp#output-template> for the available fields.
+#[derive(Debug, Deserialize)]
+struct YtDlpInfoJson {
+    id: Option<String>,
+    title: Option<String>,
+    uploader: Option<String>,
+    uploader_id: Option<String>,
+    channel: Option<String>,
+    duration: Option<f64>,
+    /// In the `YYYYMMDD` format
+    upload_date: Option<String>,
+    original_url: Option<String>,
+    webpage_url: Option<String>,
+    extractor_key: Option<String>,
+    #[serde(default)]
+    chapters: Option<Vec<YtDlpChapter>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YtDlpChapter {
+    title: Option<String>,
+    start_time: Option<f64>,
+    end_time: Option<f64>,
+}
+
+impl From<YtDlpInfoJson> for MediaInfo {
+    fn from(info: YtDlpInfoJson) -> Self {
+        Self {
+            id: info.id,
+            title: info.title,
+            uploader: info.uploader.or(info.channel),
+            uploader_id: info.uploader_id,
+            duration: info.duration,
+            upload_date: info
+                .upload_date
+                .and_then(|x| NaiveDate::parse_from_str(&x, "%Y%m%d").ok()),
+            original_url: info.original_url.or(info.webpage_url),
+            source: info.extractor_key,
+            chapters: info
+                .chapters
+                .unwrap_or_default()
+                .into_iter()
+                .map(|x| MediaChapter {
+                    title: x.title,
+                    start_time: x.start_time.unwrap_or_default(),
+                    end_time: x.end_time,
+                })
+                .collect(),
+        }
+    }
+}
+
 fn is_image_error(output: Vec<u8>) -> bool {
     let output = String::from_utf8(output).unwrap_or_default();
     let output = output.trim();
@@ -203,3 +481,31 @@ fn is_image_error(output: Vec<u8>) -> bool {
 
     output.ends_with(". Maybe an image?")
 }
+
+/// The command with header values and the proxy hidden since they can contain credentials
+fn loggable_command(cmd: &Command) -> String {
+    let cmd = cmd.as_std();
+    let mut prev_arg = None;
+
+    let args = cmd
+        .get_args()
+        .map(|x| {
+            let arg = x.to_string_lossy().into_owned();
+
+            let shown = match prev_arg.as_deref() {
+                Some("--add-header") => {
+                    let name = arg.split_once(':').map_or("", |(name, _)| name);
+
+                    format!("{name}:<hidden>")
+                }
+                Some("--proxy") => "<hidden>".to_string(),
+                _ => arg.clone(),
+            };
+
+            prev_arg = Some(arg);
+            shown
+        })
+        .collect::<Vec<_>>();
+
+    format!("{:?} {}", cmd.get_program(), args.join(" "))
+}
@@ -0,0 +1,46 @@
+//! The `Content-Range` header.
+//!
+//! # References
+//! - "Range Requests": <https://datatracker.ietf.org/doc/html/rfc9110#section-14.4>
+
+use reqwest::header;
+
+/// A satisfied byte range (eg. `bytes 200-1000/67589`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContentRange {
+    /// First byte of the range, inclusive
+    pub start: u64,
+    /// Last byte of the range, inclusive
+    pub end: u64,
+    /// Length of the full representation, if known
+    pub total: Option<u64>,
+}
+
+impl ContentRange {
+    pub fn from_raw(hv: &header::HeaderValue) -> anyhow::Result<Self> {
+        let hv = hv.to_str()?.trim();
+
+        let Some(range) = hv.strip_prefix("bytes ") else {
+            anyhow::bail!("unsupported range unit");
+        };
+        let Some((range, total)) = range.split_once('/') else {
+            anyhow::bail!("missing complete length");
+        };
+        let Some((start, end)) = range.split_once('-') else {
+            anyhow::bail!("unsatisfied range");
+        };
+
+        let start = start.trim().parse::<u64>()?;
+        let end = end.trim().parse::<u64>()?;
+        let total = match total.trim() {
+            "*" => None,
+            x => Some(x.parse::<u64>()?),
+        };
+
+        if end < start {
+            anyhow::bail!("invalid range");
+        }
+
+        Ok(Self { start, end, total })
+    }
+}
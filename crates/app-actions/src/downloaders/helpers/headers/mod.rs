@@ -1,2 +1,3 @@
 pub mod common;
 pub mod content_disposition;
+pub mod content_range;
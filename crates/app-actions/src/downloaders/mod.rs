@@ -3,6 +3,7 @@ use std::fmt::Debug;
 pub use common::{
     download_request::{DownloadRequest, DownloaderOptions},
     download_result::DownloadResult,
+    media_info::{MediaChapter, MediaInfo},
 };
 pub use handlers::DownloaderEntry;
 
@@ -31,7 +32,8 @@ pub trait Downloader: Debug + Send + Sync {
     async fn download(&self, req: &DownloadRequest) -> DownloaderReturn;
 }
 
-pub type DownloaderReturn = Result<DownloadResult, DownloaderError>;
+/// Downloaders can return multiple results for a single request (eg. playlists)
+pub type DownloaderReturn = Result<Vec<DownloadResult>, DownloaderError>;
 pub type DownloaderError = String;
 
 pub async fn download_file(file: &DownloadRequest) -> DownloaderReturn {
@@ -48,25 +50,6 @@ pub async fn download_file_with(
     downloaders: &[DownloaderEntry],
     request: &DownloadRequest,
 ) -> DownloaderReturn {
-    async fn find_downloader(
-        downloaders: &[DownloaderEntry],
-        request: &DownloadRequest,
-    ) -> Option<DownloaderEntry> {
-        if let Some(downloader) = &request.preferred_downloader {
-            if downloader.can_download(request).await {
-                return Some(downloader.clone());
-            }
-        }
-
-        for downloader in downloaders {
-            if downloader.can_download(request).await {
-                return Some(downloader.clone());
-            }
-        }
-
-        None
-    }
-
     let downloader = find_downloader(downloaders, request).await;
 
     let downloader = match downloader {
@@ -79,5 +62,29 @@ pub async fn download_file_with(
         }
     };
 
-    downloader.download(request).await
+    downloader
+        .download(&request.clone().with_defaults_for(downloader.name()))
+        .await
+}
+
+/// The downloader that would handle the request.
+///
+/// The preferred downloader of the request is used if it can download it.
+pub async fn find_downloader(
+    downloaders: &[DownloaderEntry],
+    request: &DownloadRequest,
+) -> Option<DownloaderEntry> {
+    if let Some(downloader) = &request.preferred_downloader {
+        if downloader.can_download(request).await {
+            return Some(downloader.clone());
+        }
+    }
+
+    for downloader in downloaders {
+        if downloader.can_download(request).await {
+            return Some(downloader.clone());
+        }
+    }
+
+    None
 }
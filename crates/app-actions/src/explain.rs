@@ -0,0 +1,118 @@
+//! Plans what would be done to a URL or file without downloading or modifying anything.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    downloaders::{self, DownloadRequest, DownloaderOptions, AVAILABLE_DOWNLOADERS},
+    extractors::{self, ExtractInfoRequest},
+    fixers::{FixRequest, ENABLED_FIXERS},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadPlan {
+    pub url: Url,
+    /// The extractor that matched the URL
+    pub extractor: Option<String>,
+    pub meta: HashMap<String, serde_json::Value>,
+    pub downloads: Vec<PlannedDownload>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedDownload {
+    pub url: Url,
+    /// The downloader the extractor asked for, if any
+    pub preferred_downloader: Option<String>,
+    /// The downloader that would be used or `None` if none of them can download the URL
+    pub downloader: Option<String>,
+    pub downloader_options: DownloaderOptions,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixPlan {
+    pub file_path: PathBuf,
+    /// The enabled fixers in the order they run
+    pub fixers: Vec<PlannedFixer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedFixer {
+    pub name: String,
+    /// Whether the fixer can run for the file as it is now.
+    ///
+    /// Fixers that run earlier may change the file, so this can differ from an actual run.
+    pub would_run: bool,
+}
+
+/// Extracts the info for the request and finds the downloaders that would handle it.
+pub async fn explain_url<R>(request: R, download_dir: &Path) -> Result<DownloadPlan, String>
+where
+    R: Into<ExtractInfoRequest> + Send + Sync,
+{
+    let request = request.into();
+
+    let info = extractors::extract_info(&request)
+        .await
+        .map_err(|e| format!("Failed to extract info: {e}"))?;
+
+    let mut downloads = vec![];
+    for download_request in DownloadRequest::from_extracted_info(&info, download_dir) {
+        let downloader =
+            downloaders::find_downloader(&AVAILABLE_DOWNLOADERS, &download_request).await;
+        let download_request = match &downloader {
+            Some(x) => download_request.with_defaults_for(x.name()),
+            None => download_request,
+        };
+
+        downloads.push(PlannedDownload {
+            url: download_request.url.url().clone(),
+            preferred_downloader: download_request
+                .preferred_downloader
+                .as_ref()
+                .map(|x| x.name().to_string()),
+            downloader: downloader.map(|x| x.name().to_string()),
+            downloader_options: download_request.downloader_options,
+        });
+    }
+
+    Ok(DownloadPlan {
+        url: request.url.clone(),
+        extractor: info.extractor,
+        meta: info.meta,
+        downloads,
+    })
+}
+
+/// Checks which of the enabled fixers would run for the file.
+pub async fn explain_file<R>(request: R) -> Result<FixPlan, String>
+where
+    R: Into<FixRequest> + Send + Sync,
+{
+    let request = request
+        .into()
+        .resolve_path()
+        .and_then(FixRequest::check_path)
+        .map_err(|e| format!("Failed to check file: {e}"))?;
+
+    let mut fixers = vec![];
+    for fixer in ENABLED_FIXERS.iter() {
+        fixers.push(PlannedFixer {
+            name: fixer.name().to_string(),
+            would_run: fixer.can_run_for(&request).await,
+        });
+    }
+
+    Ok(FixPlan {
+        file_path: request.file_path,
+        fixers,
+    })
+}
@@ -1,8 +1,16 @@
+use std::collections::HashMap;
+
 use http::{HeaderMap, Method};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use url::Url;
 
-use crate::common::request::{Client, RequestBuilder};
+use crate::{
+    common::request::{Client, RequestBuilder},
+    config::ActionsConfig,
+    downloaders::DownloaderOptions,
+};
+
+pub type ExtractorOptions = HashMap<String, serde_json::Value>;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ExtractInfoRequest {
@@ -11,6 +19,16 @@ pub struct ExtractInfoRequest {
     pub method: Method,
     #[serde(with = "http_serde::header_map", default)]
     pub headers: HeaderMap,
+    #[serde(default)]
+    pub extractor_options: ExtractorOptions,
+    /// Downloader options applied to every extracted URL.
+    /// Options set by the extractor take precedence.
+    #[serde(default)]
+    pub downloader_options: DownloaderOptions,
+    /// Options for a specific downloader (by name), used for the options that aren't set
+    /// by the extractor or in [`Self::downloader_options`]
+    #[serde(default)]
+    pub downloader_defaults: HashMap<String, DownloaderOptions>,
 }
 impl std::fmt::Debug for ExtractInfoRequest {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -18,6 +36,9 @@ impl std::fmt::Debug for ExtractInfoRequest {
             .field("url", &self.url.as_str())
             .field("method", &self.method)
             .field("headers", &self.headers)
+            .field("extractor_options", &self.extractor_options)
+            .field("downloader_options", &self.downloader_options)
+            .field("downloader_defaults", &self.downloader_defaults)
             .finish()
     }
 }
@@ -32,21 +53,103 @@ impl ExtractInfoRequest {
             url: url.into(),
             method: Method::GET,
             headers: HeaderMap::default(),
+            extractor_options: ExtractorOptions::new(),
+            downloader_options: DownloaderOptions::new(),
+            downloader_defaults: HashMap::new(),
         }
     }
 
+    #[must_use]
+    pub fn with_method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    #[must_use]
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    #[must_use]
+    pub fn with_extractor_option<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<serde_json::Value>,
+    {
+        self.extractor_options.insert(key.into(), value.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_extractor_options<T>(mut self, options: T) -> Self
+    where
+        T: Into<ExtractorOptions>,
+    {
+        self.extractor_options = options.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_downloader_option<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<serde_json::Value>,
+    {
+        self.downloader_options.insert(key.into(), value.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_downloader_options<T>(mut self, options: T) -> Self
+    where
+        T: Into<DownloaderOptions>,
+    {
+        self.downloader_options = options.into();
+        self
+    }
+
+    /// Default options that are only used if the URL is downloaded by the named downloader
+    #[must_use]
+    pub fn with_downloader_defaults<T>(mut self, downloader: &str, options: T) -> Self
+    where
+        T: Into<DownloaderOptions>,
+    {
+        self.downloader_defaults
+            .insert(downloader.to_string(), options.into());
+        self
+    }
+
+    #[must_use]
+    pub fn extractor_option<T>(&self, key: &str) -> Option<T>
+    where
+        T: DeserializeOwned,
+    {
+        let val = self.extractor_options.get(key)?.clone();
+
+        serde_json::from_value(val).ok()
+    }
+
+    #[must_use]
+    pub fn extractor_options<T>(&self) -> Option<T>
+    where
+        T: DeserializeOwned,
+    {
+        let val = serde_json::to_value(self.extractor_options.clone()).ok()?;
+
+        serde_json::from_value(val).ok()
+    }
+
     pub fn as_request_builder(&self) -> Result<RequestBuilder, String> {
-        let mut builder = Client::base()?.request(
-            self.method
-                .as_str()
-                .parse()
-                .expect("Failed to parse method"),
-            self.url.as_str(),
-        );
-
-        for (k, v) in &self.headers {
-            builder = builder.header(k, v);
-        }
+        let builder = Client::base()?
+            .request(
+                self.method
+                    .as_str()
+                    .parse()
+                    .expect("Failed to parse method"),
+                self.url.as_str(),
+            )
+            .headers(ActionsConfig::credentials().apply(&self.url, &self.headers));
 
         Ok(builder)
     }
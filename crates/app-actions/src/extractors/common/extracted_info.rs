@@ -2,7 +2,7 @@ use std::{collections::HashMap, sync::Arc};
 
 use serde::{Deserialize, Serialize};
 
-use super::extract_info_request::ExtractInfoRequest;
+use super::{extract_info_request::ExtractInfoRequest, post_metadata::PostMetadata};
 use crate::{
     common::url::UrlWithMeta,
     downloaders::{Downloader, DownloaderOptions},
@@ -13,6 +13,11 @@ pub struct ExtractedInfo {
     pub request: ExtractInfoRequest,
     pub urls: Vec<ExtractedUrlInfo>,
     pub meta: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub post_metadata: Option<PostMetadata>,
+    /// Name of the extractor that produced the info
+    #[serde(default)]
+    pub extractor: Option<String>,
 }
 impl ExtractedInfo {
     #[must_use]
@@ -25,6 +30,8 @@ impl ExtractedInfo {
             request: req.clone(),
             urls: urls.into_iter().map(Into::into).collect(),
             meta: HashMap::new(),
+            post_metadata: None,
+            extractor: None,
         }
     }
 
@@ -90,6 +97,21 @@ impl ExtractedInfo {
         self
     }
 
+    #[must_use]
+    pub fn with_post_metadata(mut self, post_metadata: Option<PostMetadata>) -> Self {
+        self.post_metadata = post_metadata;
+        self
+    }
+
+    #[must_use]
+    pub fn with_extractor<S>(mut self, extractor: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.extractor = Some(extractor.into());
+        self
+    }
+
     #[must_use]
     pub fn dedup_urls(mut self) -> Self {
         self.urls.dedup();
@@ -1,2 +1,4 @@
 pub mod extract_info_request;
 pub mod extracted_info;
+pub mod post_metadata;
+pub mod short_link;
@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Information about the post the media was extracted from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PostMetadata {
+    /// The handle of the author (eg. `@username`), without the `@`
+    pub author_handle: Option<String>,
+    pub author_display_name: Option<String>,
+    pub text: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    /// The URL of the post itself
+    pub source_url: Option<String>,
+    pub engagement: PostEngagement,
+}
+impl PostMetadata {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_author_handle<S>(mut self, author_handle: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.author_handle = author_handle.map(Into::into);
+        self
+    }
+
+    #[must_use]
+    pub fn with_author_display_name<S>(mut self, author_display_name: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.author_display_name = author_display_name.map(Into::into);
+        self
+    }
+
+    #[must_use]
+    pub fn with_text<S>(mut self, text: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.text = text.map(Into::into);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_created_at(mut self, created_at: Option<DateTime<Utc>>) -> Self {
+        self.created_at = created_at;
+        self
+    }
+
+    #[must_use]
+    pub fn with_source_url<S>(mut self, source_url: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.source_url = source_url.map(Into::into);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_engagement(mut self, engagement: PostEngagement) -> Self {
+        self.engagement = engagement;
+        self
+    }
+}
+
+/// Engagement counts of the post at the time of extraction.
+/// Counts the platform doesn't provide are left empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PostEngagement {
+    pub likes: Option<u64>,
+    pub reposts: Option<u64>,
+    pub replies: Option<u64>,
+    pub quotes: Option<u64>,
+    pub views: Option<u64>,
+}
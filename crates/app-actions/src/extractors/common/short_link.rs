@@ -0,0 +1,199 @@
+use app_helpers::ip::url_resolves_to_valid_ip;
+use http::{header, HeaderMap, Method};
+use reqwest::redirect::Policy;
+use tracing::{debug, trace, warn};
+use url::Url;
+
+use super::extract_info_request::ExtractInfoRequest;
+use crate::{
+    canonical_url::{canonicalize_url, strip_tracking_params},
+    common::request::{Client, RequestClient},
+};
+
+const MAX_REDIRECT_HOPS: usize = 10;
+
+/// Hosts that only redirect to the actual content
+const SHORT_LINK_HOSTS: &[&str] = &[
+    "t.co",
+    "bit.ly",
+    "tinyurl.com",
+    "goo.gl",
+    "ow.ly",
+    "buff.ly",
+    "is.gd",
+    "t.ly",
+    "rb.gy",
+    "dlvr.it",
+    "shorturl.at",
+    "vm.tiktok.com",
+    "vt.tiktok.com",
+    "redd.it",
+    "pin.it",
+    "youtu.be",
+    "gph.is",
+    "spoti.fi",
+    "on.soundcloud.com",
+    "fb.watch",
+];
+
+#[must_use]
+pub fn is_short_link(url: &Url) -> bool {
+    url.host_str()
+        .is_some_and(|x| SHORT_LINK_HOSTS.contains(&x.trim_start_matches("www.")))
+}
+
+/// Canonicalizes the request URL and resolves it if it's a known short link.
+/// Returns the resolved request and the redirect chain (empty if the URL wasn't resolved).
+pub async fn resolve_request(
+    request: &ExtractInfoRequest,
+) -> Result<(ExtractInfoRequest, Vec<Url>), String> {
+    let mut request = request.clone();
+    request.url = canonicalize_url(&request.url);
+
+    if !is_short_link(&request.url) {
+        return Ok((request, vec![]));
+    }
+
+    let chain = resolve_redirect_chain(&request).await?;
+
+    if let Some(url) = chain.last() {
+        request = with_resolved_url(&request, canonicalize_url(url));
+    }
+
+    Ok((request, chain))
+}
+
+/// Follows the redirects of a short link (eg. `https://pin.it/...`)
+/// and returns a copy of the request pointing to the final URL.
+pub async fn resolve_short_link(
+    request: &ExtractInfoRequest,
+) -> Result<ExtractInfoRequest, String> {
+    let chain = resolve_redirect_chain(request).await?;
+
+    Ok(chain.last().map_or_else(
+        || request.clone(),
+        |url| with_resolved_url(request, url.clone()),
+    ))
+}
+
+/// The headers of the request are only kept if the URL stays on the same host
+/// so things like cookies don't get sent to wherever the short link points to
+fn with_resolved_url(request: &ExtractInfoRequest, url: Url) -> ExtractInfoRequest {
+    let mut resolved = request.clone();
+
+    if !is_same_host(&request.url, &url) {
+        resolved.headers = HeaderMap::new();
+    }
+    resolved.url = url;
+
+    resolved
+}
+
+fn is_same_host(a: &Url, b: &Url) -> bool {
+    a.host_str().is_some() && a.host_str() == b.host_str()
+}
+
+/// Follows the redirects of the request URL one hop at a time.
+/// Every hop must resolve to a public IP.
+/// The returned chain starts with the (cleaned up) request URL and ends with the final URL.
+#[tracing::instrument]
+pub async fn resolve_redirect_chain(request: &ExtractInfoRequest) -> Result<Vec<Url>, String> {
+    let client = Client::builder()
+        .redirect(Policy::none())
+        .build()
+        .map_err(|e| format!("Failed to create client: {:?}", e))?;
+
+    let mut current = request.url.clone();
+    strip_tracking_params(&mut current);
+
+    let mut chain = vec![current.clone()];
+
+    loop {
+        validate_hop(&current).await?;
+
+        let location = match get_redirect_location(&client, request, &current).await {
+            Ok(Some(x)) => x,
+            Ok(None) => break,
+            Err(e) => {
+                warn!(?e, url = ?current.as_str(), "Failed to resolve redirect");
+                break;
+            }
+        };
+
+        if chain.len() > MAX_REDIRECT_HOPS {
+            return Err(format!(
+                "Too many redirects (more than {MAX_REDIRECT_HOPS}) for {:?}",
+                request.url.as_str()
+            ));
+        }
+
+        let mut next = current
+            .join(&location)
+            .map_err(|e| format!("Invalid redirect location {location:?}: {e:?}"))?;
+        strip_tracking_params(&mut next);
+        trace!(next = ?next.as_str(), "Got redirect");
+
+        if chain.contains(&next) {
+            debug!("Redirect loop detected");
+            break;
+        }
+
+        chain.push(next.clone());
+        current = next;
+    }
+
+    debug!(chain = ?chain.iter().map(Url::as_str).collect::<Vec<_>>(), "Resolved redirects");
+
+    Ok(chain)
+}
+
+async fn validate_hop(url: &Url) -> Result<(), String> {
+    let url_str = url.to_string();
+
+    tokio::task::spawn_blocking(move || url_resolves_to_valid_ip(&url_str))
+        .await
+        .map_err(|e| format!("Failed to validate URL: {e:?}"))?
+        .map_err(|e| format!("Redirect to invalid URL {:?}: {e}", url.as_str()))?;
+
+    Ok(())
+}
+
+/// Tries a HEAD request first since it's cheaper,
+/// but some servers don't support it so fall back to GET.
+async fn get_redirect_location(
+    client: &RequestClient,
+    request: &ExtractInfoRequest,
+    url: &Url,
+) -> Result<Option<String>, String> {
+    // Same as reqwest's own redirect handling, don't leak the headers to other hosts
+    let headers = if is_same_host(&request.url, url) {
+        request.headers.clone()
+    } else {
+        HeaderMap::new()
+    };
+
+    let send = |method: Method| {
+        client
+            .request(method, url.as_str())
+            .headers(headers.clone())
+            .send()
+    };
+
+    let resp = match send(Method::HEAD).await {
+        Ok(x) if x.status().is_success() || x.status().is_redirection() => x,
+        _ => send(Method::GET)
+            .await
+            .map_err(|e| format!("Failed to send request: {e:?}"))?,
+    };
+    trace!(?resp, "Got redirect response");
+
+    if !resp.status().is_redirection() {
+        return Ok(None);
+    }
+
+    Ok(resp
+        .headers()
+        .get(header::LOCATION)
+        .and_then(|x| x.to_str().ok())
+        .map(ToString::to_string))
+}
@@ -1,15 +1,16 @@
-use std::sync::LazyLock;
+use std::{collections::HashMap, sync::LazyLock};
 
+use chrono::{DateTime, Utc};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 use url::Url;
 
 use super::{twitter::Twitter, ExtractInfoRequest, ExtractedInfo, Extractor};
 use crate::{
-    common::request::Client,
+    common::{request::Client, url::validate_public_url},
     downloaders::handlers::{generic::Generic, yt_dlp::YtDlp},
-    extractors::ExtractedUrlInfo,
+    extractors::{ExtractedUrlInfo, PostEngagement, PostMetadata},
 };
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -27,16 +28,22 @@ impl Extractor for Bsky {
     }
 
     async fn extract_info(&self, request: &ExtractInfoRequest) -> Result<ExtractedInfo, String> {
-        let mut urls = match Self::get_bsky_media_urls(&request.url).await {
-            Ok(urls) => urls,
+        let thread = match Self::get_post_thread(&request.url).await {
+            Ok(thread) => thread,
             Err(e) => {
                 return Err(format!("Failed to get bsky media urls: {e}"));
             }
         };
 
+        let post_metadata = thread.post_metadata(request.url.as_str());
+
+        trace!(?post_metadata, "Got post metadata");
+
+        let mut urls = Self::thread_media_urls(&thread).await;
+
         urls.push(Twitter.screenshot_tweet_url_info(request.url.as_str()));
 
-        Ok(ExtractedInfo::from_urls(request, urls))
+        Ok(ExtractedInfo::from_urls(request, urls).with_post_metadata(post_metadata))
     }
 }
 
@@ -45,6 +52,24 @@ impl Bsky {
     pub async fn get_bsky_media_urls(post_url: &Url) -> Result<Vec<ExtractedUrlInfo>, String> {
         debug!("Getting bsky media urls for post url");
 
+        let thread = Self::get_post_thread(post_url).await?;
+
+        Ok(Self::thread_media_urls(&thread).await)
+    }
+
+    async fn thread_media_urls(thread: &GetPostThreadResponse) -> Vec<ExtractedUrlInfo> {
+        let media = thread.get_media();
+
+        trace!(?media, "Got media from post");
+
+        let media = resolve_blob_urls(media).await;
+
+        trace!(?media, "Resolved media urls");
+
+        media
+    }
+
+    async fn get_post_thread(post_url: &Url) -> Result<GetPostThreadResponse, String> {
         let Some(parts) = BSKY_PATH_MATCHER.captures(post_url.path()) else {
             return Err("Invalid bsky post url".to_string());
         };
@@ -53,7 +78,12 @@ impl Bsky {
             return Err("Invalid bsky post url. No post id".to_string());
         };
 
-        let Some(username) = parts.name("username").map(|x| x.as_str()) else {
+        // Profiles can be linked by handle or by DID (eg. `did:plc:...`),
+        // the latter is sometimes percent-encoded
+        let Some(username) = parts
+            .name("username")
+            .map(|x| percent_encoding::percent_decode_str(x.as_str()).decode_utf8_lossy())
+        else {
             return Err("Invalid bsky post url. No username".to_string());
         };
 
@@ -87,11 +117,7 @@ impl Bsky {
 
         trace!(?resp, "Got response from bsky api");
 
-        let media = resp.get_media();
-
-        trace!(?media, "Got media from post");
-
-        Ok(media)
+        Ok(resp)
     }
 }
 
@@ -117,23 +143,197 @@ impl Bsky {
     }
 }
 
+/// A media item found in a post.
+/// Blobs are fetched from the author's PDS if possible since
+/// the CDN only serves resized images and the video as a HLS playlist.
+#[derive(Debug, Clone)]
+struct PostMedia {
+    did: String,
+    blob_cid: Option<String>,
+    fallback: ExtractedUrlInfo,
+}
+impl PostMedia {
+    fn new(did: &str, blob_cid: Option<String>, fallback: ExtractedUrlInfo) -> Self {
+        Self {
+            did: did.to_string(),
+            blob_cid,
+            fallback,
+        }
+    }
+}
+
+async fn resolve_blob_urls(media: Vec<PostMedia>) -> Vec<ExtractedUrlInfo> {
+    let mut pds_endpoints = HashMap::<String, Option<Url>>::new();
+    let mut urls = Vec::with_capacity(media.len());
+
+    for item in media {
+        let Some(blob_cid) = &item.blob_cid else {
+            // Link cards can point anywhere
+            match validate_public_url(item.fallback.url.url().as_str()).await {
+                Ok(_) => urls.push(item.fallback),
+                Err(e) => warn!(?e, "Skipping link card media"),
+            }
+            continue;
+        };
+
+        if !pds_endpoints.contains_key(&item.did) {
+            let endpoint = match get_pds_endpoint(&item.did).await {
+                Ok(x) => Some(x),
+                Err(e) => {
+                    warn!(?e, did = ?item.did, "Failed to get PDS endpoint");
+                    None
+                }
+            };
+
+            pds_endpoints.insert(item.did.clone(), endpoint);
+        }
+
+        let blob_url = pds_endpoints
+            .get(&item.did)
+            .cloned()
+            .flatten()
+            .and_then(|x| x.join("/xrpc/com.atproto.sync.getBlob").ok())
+            .map(|mut x| {
+                x.query_pairs_mut()
+                    .extend_pairs([("did", item.did.as_str()), ("cid", blob_cid.as_str())]);
+
+                x
+            });
+
+        match blob_url {
+            Some(x) => {
+                urls.push(
+                    ExtractedUrlInfo::new(x.as_str()).with_preferred_downloader(Some(Generic)),
+                );
+            }
+            None => urls.push(item.fallback),
+        }
+    }
+
+    urls
+}
+
+#[tracing::instrument]
+async fn get_pds_endpoint(did: &str) -> Result<Url, String> {
+    let did_document_url = if let Some(domain) = did.strip_prefix("did:web:") {
+        // The domain is chosen by the author so make sure it's not something internal
+        validate_public_url(&format!("https://{domain}/.well-known/did.json"))
+            .await
+            .map_err(|e| format!("Invalid DID document URL: {e}"))?
+            .to_string()
+    } else if did.starts_with("did:plc:") {
+        format!("https://plc.directory/{did}")
+    } else {
+        return Err(format!("Unsupported DID method: {did:?}"));
+    };
+
+    let did_document = Client::base()?
+        .get(did_document_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to get DID document. Error: {e}"))?
+        .error_for_status()
+        .map_err(|e| format!("Failed to get DID document. Error: {e}"))?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse DID document. Error: {e}"))?;
+
+    trace!(?did_document, "Got DID document");
+
+    let endpoint = did_document
+        .get("service")
+        .and_then(serde_json::Value::as_array)
+        .and_then(|services| {
+            services.iter().find(|x| {
+                x.get("id")
+                    .and_then(serde_json::Value::as_str)
+                    .is_some_and(|x| x.ends_with("#atproto_pds"))
+            })
+        })
+        .and_then(|x| x.get("serviceEndpoint"))
+        .and_then(serde_json::Value::as_str)
+        .map(ToString::to_string)
+        .ok_or_else(|| "No PDS endpoint in DID document".to_string())?;
+
+    // The DID document is user controlled so make sure the PDS is not something internal
+    validate_public_url(&endpoint)
+        .await
+        .map_err(|e| format!("Invalid PDS endpoint: {e}"))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct GetPostThreadResponse {
-    thread: GetPostThreadResponseThread,
+    thread: ThreadItem,
 }
 impl GetPostThreadResponse {
-    pub fn get_media(&self) -> Vec<ExtractedUrlInfo> {
+    pub fn get_media(&self) -> Vec<PostMedia> {
         match &self.thread {
-            GetPostThreadResponseThread::ThreadViewPost { post } => post.get_media(),
+            ThreadItem::ThreadViewPost(thread) => thread
+                .self_thread_posts()
+                .into_iter()
+                .flat_map(PostView::get_media)
+                .collect(),
+            ThreadItem::Other => vec![],
+        }
+    }
+
+    fn post_metadata(&self, source_url: &str) -> Option<PostMetadata> {
+        match &self.thread {
+            ThreadItem::ThreadViewPost(thread) => Some(thread.post.post_metadata(source_url)),
+            ThreadItem::Other => None,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "$type")]
-enum GetPostThreadResponseThread {
+enum ThreadItem {
     #[serde(rename = "app.bsky.feed.defs#threadViewPost")]
-    ThreadViewPost { post: PostView },
+    ThreadViewPost(Box<ThreadViewPost>),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ThreadViewPost {
+    post: PostView,
+    #[serde(default)]
+    parent: Option<Box<ThreadItem>>,
+    #[serde(default)]
+    replies: Vec<ThreadItem>,
+}
+impl ThreadViewPost {
+    /// The post along with the surrounding posts of the thread written by the same author,
+    /// ordered from the oldest to the newest.
+    fn self_thread_posts(&self) -> Vec<&PostView> {
+        let author = &self.post.author.did;
+
+        let mut posts = vec![];
+
+        let mut parent = self.parent.as_deref();
+        while let Some(ThreadItem::ThreadViewPost(thread)) = parent {
+            if thread.post.author.did != *author {
+                break;
+            }
+
+            posts.push(&thread.post);
+            parent = thread.parent.as_deref();
+        }
+
+        posts.reverse();
+        posts.push(&self.post);
+
+        let mut current = self;
+        while let Some(reply) = current.replies.iter().find_map(|x| match x {
+            ThreadItem::ThreadViewPost(x) if x.post.author.did == *author => Some(x),
+            _ => None,
+        }) {
+            posts.push(&reply.post);
+            current = reply;
+        }
+
+        posts
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -142,18 +342,55 @@ struct PostView {
     uri: String,
     cid: String,
     author: ProfileViewBasic,
-    embed: Option<PostViewEmbed>,
+    embed: Option<EmbedView>,
     indexed_at: String,
+    #[serde(default)]
+    record: PostRecord,
+    like_count: Option<u64>,
+    repost_count: Option<u64>,
+    reply_count: Option<u64>,
+    quote_count: Option<u64>,
 }
 impl PostView {
-    pub fn get_media(&self) -> Vec<ExtractedUrlInfo> {
-        match &self.embed {
-            Some(PostViewEmbed::RecordWithMedia { media, .. } | PostViewEmbed::Media(media)) => {
-                media.get_media()
-            }
-            _ => vec![],
-        }
+    fn post_metadata(&self, source_url: &str) -> PostMetadata {
+        let created_at = self
+            .record
+            .created_at
+            .as_deref()
+            .unwrap_or(&self.indexed_at);
+
+        PostMetadata::new()
+            .with_author_handle(Some(&self.author.handle))
+            .with_author_display_name(self.author.display_name.as_ref())
+            .with_text(self.record.text.as_ref())
+            .with_created_at(
+                DateTime::parse_from_rfc3339(created_at)
+                    .ok()
+                    .map(|x| x.with_timezone(&Utc)),
+            )
+            .with_source_url(Some(source_url))
+            .with_engagement(PostEngagement {
+                likes: self.like_count,
+                reposts: self.repost_count,
+                replies: self.reply_count,
+                quotes: self.quote_count,
+                views: None,
+            })
     }
+
+    pub fn get_media(&self) -> Vec<PostMedia> {
+        self.embed
+            .as_ref()
+            .map(|x| x.get_media(&self.author.did))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PostRecord {
+    text: Option<String>,
+    created_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -166,7 +403,7 @@ struct ProfileViewBasic {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "$type")]
-enum PostMedia {
+enum EmbedView {
     #[serde(rename = "app.bsky.embed.images#view", rename_all = "camelCase")]
     Images { images: Vec<ViewImage> },
     #[serde(rename = "app.bsky.embed.video#view", rename_all = "camelCase")]
@@ -179,35 +416,89 @@ enum PostMedia {
     },
     #[serde(rename = "app.bsky.embed.external#view", rename_all = "camelCase")]
     External { external: ViewExternal },
+    #[serde(rename = "app.bsky.embed.record#view", rename_all = "camelCase")]
+    Record { record: EmbedRecord },
+    #[serde(
+        rename = "app.bsky.embed.recordWithMedia#view",
+        rename_all = "camelCase"
+    )]
+    RecordWithMedia {
+        record: RecordView,
+        media: Box<Self>,
+    },
+    #[serde(other)]
+    Other,
 }
-impl PostMedia {
-    pub fn get_media(&self) -> Vec<ExtractedUrlInfo> {
+impl EmbedView {
+    /// Media of the embed, including media of quoted posts.
+    /// `did` is the DID of the author of the post containing the embed.
+    pub fn get_media(&self, did: &str) -> Vec<PostMedia> {
         match self {
             Self::Images { images, .. } => images
                 .iter()
                 .map(|x| {
-                    ExtractedUrlInfo::new(&x.fullsize).with_preferred_downloader(Some(Generic))
+                    PostMedia::new(
+                        did,
+                        x.blob_cid(),
+                        ExtractedUrlInfo::new(&x.fullsize).with_preferred_downloader(Some(Generic)),
+                    )
                 })
                 .collect(),
-            Self::Video { playlist, .. } => {
-                vec![ExtractedUrlInfo::new(playlist.as_str()).with_preferred_downloader(Some(YtDlp))]
+            Self::Video { cid, playlist, .. } => {
+                vec![PostMedia::new(
+                    did,
+                    Some(cid.clone()),
+                    ExtractedUrlInfo::new(playlist.as_str()).with_preferred_downloader(Some(YtDlp)),
+                )]
             }
-            Self::External { .. } => vec![],
+            Self::External { external } => external
+                .get_media()
+                .map(|x| PostMedia::new(did, None, x))
+                .into_iter()
+                .collect(),
+            Self::Record { record } => record.get_media(),
+            Self::RecordWithMedia { record, media } => {
+                let mut res = media.get_media(did);
+                res.extend(record.record.get_media());
+                res
+            }
+            Self::Other => vec![],
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
-enum PostViewEmbed {
-    Media(PostMedia),
-    Record {
-        record: serde_json::Value,
-    },
-    RecordWithMedia {
-        record: serde_json::Value,
-        media: PostMedia,
-    },
+struct RecordView {
+    record: EmbedRecord,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "$type")]
+enum EmbedRecord {
+    #[serde(rename = "app.bsky.embed.record#viewRecord")]
+    ViewRecord(ViewRecord),
+    #[serde(other)]
+    Other,
+}
+impl EmbedRecord {
+    pub fn get_media(&self) -> Vec<PostMedia> {
+        match self {
+            Self::ViewRecord(record) => record
+                .embeds
+                .iter()
+                .flat_map(|x| x.get_media(&record.author.did))
+                .collect(),
+            Self::Other => vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ViewRecord {
+    uri: String,
+    author: ProfileViewBasic,
+    #[serde(default)]
+    embeds: Vec<EmbedView>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -223,11 +514,52 @@ struct ViewImage {
     alt: String,
     aspect_ratio: Option<AspectRatio>,
 }
+impl ViewImage {
+    /// The CDN URLs look like `https://cdn.bsky.app/img/feed_fullsize/plain/<did>/<cid>@jpeg`
+    fn blob_cid(&self) -> Option<String> {
+        let url = Url::parse(&self.fullsize).ok()?;
+        let file_name = url.path_segments()?.next_back()?;
+
+        file_name
+            .split('@')
+            .next()
+            .filter(|x| !x.is_empty())
+            .map(ToString::to_string)
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct ViewExternal {
     uri: String,
+    #[serde(default)]
     title: String,
+    #[serde(default)]
     description: String,
     thumb: Option<String>,
 }
+impl ViewExternal {
+    /// Link cards that point directly to media (eg. GIFs from Tenor) are downloaded as is,
+    /// otherwise the card thumbnail is used.
+    fn get_media(&self) -> Option<ExtractedUrlInfo> {
+        let is_media_link = Url::parse(&self.uri).is_ok_and(|url| {
+            url.host_str() == Some("media.tenor.com")
+                || std::path::Path::new(url.path())
+                    .extension()
+                    .and_then(|x| x.to_str())
+                    .is_some_and(|x| {
+                        matches!(
+                            x.to_lowercase().as_str(),
+                            "gif" | "mp4" | "webm" | "jpg" | "jpeg" | "png" | "webp"
+                        )
+                    })
+        });
+
+        if is_media_link {
+            return Some(ExtractedUrlInfo::new(&self.uri).with_preferred_downloader(Some(Generic)));
+        }
+
+        self.thumb
+            .as_ref()
+            .map(|x| ExtractedUrlInfo::new(x.as_str()).with_preferred_downloader(Some(Generic)))
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use url::Url;
+
+use super::GifSiteExtractorOptions;
+use crate::{
+    downloaders::handlers::generic::Generic,
+    extractors::{ExtractInfoRequest, ExtractedInfo, Extractor},
+};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Giphy;
+
+#[async_trait::async_trait]
+#[typetag::serde]
+impl Extractor for Giphy {
+    fn description(&self) -> &'static str {
+        "Get the original GIF/MP4 from Giphy pages"
+    }
+
+    async fn can_handle(&self, request: &ExtractInfoRequest) -> bool {
+        Self::gif_id_from_url(&request.url).is_some()
+    }
+
+    async fn extract_info(&self, request: &ExtractInfoRequest) -> Result<ExtractedInfo, String> {
+        let options = request
+            .extractor_options::<GifSiteExtractorOptions>()
+            .unwrap_or_default();
+
+        let gif_id = Self::gif_id_from_url(&request.url)
+            .ok_or_else(|| "Failed to get GIF ID from giphy URL".to_string())?;
+        debug!(?gif_id, "Got giphy GIF ID");
+
+        let extension = if options.prefer_gif { "gif" } else { "mp4" };
+        let media_url = format!("https://i.giphy.com/media/{gif_id}/giphy.{extension}");
+
+        Ok(ExtractedInfo::from_url(request, media_url.as_str())
+            .with_preferred_downloader(Some(Generic)))
+    }
+}
+
+static PAGE_PATH_MATCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^/(gifs|stickers|clips)/(.+-)?(?P<id>[a-zA-Z0-9]+)/?$").expect("Invalid regex")
+});
+
+static MEDIA_PATH_MATCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^/(embed|media|media/v1\.[^/]+)/(?P<id>[a-zA-Z0-9]+)(/|$)").expect("Invalid regex")
+});
+
+impl Giphy {
+    #[must_use]
+    pub fn is_giphy_url(url: &Url) -> bool {
+        url.domain()
+            .is_some_and(|x| x == "giphy.com" || x.ends_with(".giphy.com"))
+    }
+
+    fn gif_id_from_url(url: &Url) -> Option<String> {
+        if !Self::is_giphy_url(url) {
+            return None;
+        }
+
+        let path = url.path();
+
+        PAGE_PATH_MATCH
+            .captures(path)
+            .or_else(|| MEDIA_PATH_MATCH.captures(path))
+            .and_then(|x| x.name("id"))
+            .map(|x| x.as_str().to_string())
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+use url::Url;
+
+use super::{get_oembed, GifSiteExtractorOptions};
+use crate::{
+    downloaders::handlers::generic::Generic,
+    extractors::{ExtractInfoRequest, ExtractedInfo, Extractor},
+};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Imgflip;
+
+#[async_trait::async_trait]
+#[typetag::serde]
+impl Extractor for Imgflip {
+    fn description(&self) -> &'static str {
+        "Get the original image/GIF/MP4 from Imgflip pages"
+    }
+
+    async fn can_handle(&self, request: &ExtractInfoRequest) -> bool {
+        Self::is_post_url(&request.url)
+    }
+
+    async fn extract_info(&self, request: &ExtractInfoRequest) -> Result<ExtractedInfo, String> {
+        let options = request
+            .extractor_options::<GifSiteExtractorOptions>()
+            .unwrap_or_default();
+
+        let captures = PATH_MATCH
+            .captures(request.url.path())
+            .ok_or_else(|| "URL is not a valid Imgflip post".to_string())?;
+        let is_gif = captures.name("kind").is_some_and(|x| x.as_str() == "gif");
+        let post_id = captures
+            .name("id")
+            .map(|x| x.as_str())
+            .ok_or_else(|| "URL is not a valid Imgflip post".to_string())?;
+        debug!(?post_id, is_gif, "Got imgflip post ID");
+
+        let media_url = if is_gif {
+            let extension = if options.prefer_gif { "gif" } else { "mp4" };
+
+            format!("https://i.imgflip.com/{post_id}.{extension}")
+        } else {
+            let oembed = get_oembed("https://imgflip.com/oembed", &request.url).await?;
+            trace!(?oembed, "Got oEmbed data from imgflip");
+
+            oembed
+                .url
+                .ok_or_else(|| "Failed to get image URL from imgflip".to_string())?
+        };
+
+        Ok(ExtractedInfo::from_url(request, media_url.as_str())
+            .with_preferred_downloader(Some(Generic)))
+    }
+}
+
+static PATH_MATCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^/(?P<kind>i|gif)/(?P<id>[a-zA-Z0-9]+)/?$").expect("Invalid regex")
+});
+
+impl Imgflip {
+    #[must_use]
+    pub fn is_post_url(url: &Url) -> bool {
+        url.domain()
+            .is_some_and(|x| x == "imgflip.com" || x == "www.imgflip.com")
+            && PATH_MATCH.is_match(url.path())
+    }
+}
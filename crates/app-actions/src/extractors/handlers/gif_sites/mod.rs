@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+use url::Url;
+
+use crate::{common::request::Client, extractors::ExtractorOptions};
+
+pub mod giphy;
+pub mod imgflip;
+pub mod tenor;
+
+/// Options shared by the GIF platform extractors
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct GifSiteExtractorOptions {
+    /// Get the GIF file instead of the (much smaller) MP4 version
+    prefer_gif: bool,
+}
+impl GifSiteExtractorOptions {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub const fn with_prefer_gif(mut self, prefer_gif: bool) -> Self {
+        self.prefer_gif = prefer_gif;
+        self
+    }
+}
+impl From<GifSiteExtractorOptions> for ExtractorOptions {
+    fn from(val: GifSiteExtractorOptions) -> Self {
+        let val = serde_json::to_value(val)
+            .ok()
+            .and_then(|x| x.as_object().cloned())
+            .expect("Failed to serialize options");
+
+        val.into_iter().collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OEmbedResponse {
+    url: Option<String>,
+}
+
+async fn get_oembed(endpoint: &str, url: &Url) -> Result<OEmbedResponse, String> {
+    let mut oembed_url = Url::parse(endpoint).map_err(|e| format!("Invalid oEmbed URL: {e:?}"))?;
+    oembed_url
+        .query_pairs_mut()
+        .extend_pairs([("url", url.as_str()), ("format", "json")]);
+
+    trace!(oembed_url = ?oembed_url.as_str(), "Fetching oEmbed data");
+
+    Client::base()?
+        .get(oembed_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send oEmbed request: {e:?}"))?
+        .error_for_status()
+        .map_err(|e| format!("Failed to get oEmbed data: {e:?}"))?
+        .json::<OEmbedResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse oEmbed data: {e:?}"))
+}
@@ -0,0 +1,124 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+use url::Url;
+
+use super::GifSiteExtractorOptions;
+use crate::{
+    downloaders::handlers::generic::Generic,
+    extractors::{ExtractInfoRequest, ExtractedInfo, Extractor},
+};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Tenor;
+
+#[async_trait::async_trait]
+#[typetag::serde]
+impl Extractor for Tenor {
+    fn description(&self) -> &'static str {
+        "Get the original GIF/MP4 from Tenor pages"
+    }
+
+    async fn can_handle(&self, request: &ExtractInfoRequest) -> bool {
+        Self::is_view_url(&request.url)
+    }
+
+    async fn extract_info(&self, request: &ExtractInfoRequest) -> Result<ExtractedInfo, String> {
+        let options = request
+            .extractor_options::<GifSiteExtractorOptions>()
+            .unwrap_or_default();
+
+        let gif_id = VIEW_PATH_MATCH
+            .captures(request.url.path())
+            .and_then(|x| x.name("id"))
+            .map(|x| x.as_str().to_string())
+            .ok_or_else(|| "URL is not a valid Tenor GIF".to_string())?;
+        debug!(?gif_id, "Got tenor GIF ID");
+
+        let store_data = get_store_data(request).await?;
+
+        let media_formats = find_media_formats(&store_data, &gif_id)
+            .ok_or_else(|| "Failed to find GIF in tenor page data".to_string())?;
+        trace!(?media_formats, "Got tenor media formats");
+
+        let format_url = |format: &str| {
+            media_formats
+                .get(format)
+                .and_then(|x| x.get("url"))
+                .and_then(serde_json::Value::as_str)
+        };
+
+        let media_url = if options.prefer_gif {
+            format_url("gif").or_else(|| format_url("mp4"))
+        } else {
+            format_url("mp4").or_else(|| format_url("gif"))
+        }
+        .ok_or_else(|| "Failed to find media URL in tenor page data".to_string())?;
+
+        Ok(ExtractedInfo::from_url(request, media_url).with_preferred_downloader(Some(Generic)))
+    }
+}
+
+static VIEW_PATH_MATCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(/[a-z]{2}(-[A-Z]{2})?)?/view/(.+-)?(?P<id>\d+)/?$").expect("Invalid regex")
+});
+
+impl Tenor {
+    #[must_use]
+    pub fn is_view_url(url: &Url) -> bool {
+        url.domain()
+            .is_some_and(|x| x == "tenor.com" || x == "www.tenor.com")
+            && VIEW_PATH_MATCH.is_match(url.path())
+    }
+}
+
+async fn get_store_data(request: &ExtractInfoRequest) -> Result<serde_json::Value, String> {
+    let resp = request
+        .as_request_builder()?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to tenor: {:?}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to get text from tenor response: {:?}", e))?;
+
+    trace!("Got response from tenor");
+
+    let store_data = tokio::task::spawn_blocking(move || {
+        let dom = tl::parse(&resp, tl::ParserOptions::default())
+            .map_err(|e| format!("Failed to parse html from tenor: {:?}", e))?;
+        let parser = dom.parser();
+
+        dom.get_element_by_id("store-cache")
+            .and_then(|x| x.get(parser))
+            .map(|x| x.inner_text(parser).to_string())
+            .ok_or_else(|| "Failed to find page data in tenor response".to_string())
+    })
+    .await
+    .map_err(|e| format!("Failed to get page data from tenor: {:?}", e))??;
+
+    serde_json::from_str(&store_data)
+        .map_err(|e| format!("Failed to parse page data from tenor: {:?}", e))
+}
+
+/// Looks for the GIF object with the given ID and returns its `media_formats`
+fn find_media_formats<'a>(
+    value: &'a serde_json::Value,
+    gif_id: &str,
+) -> Option<&'a serde_json::Value> {
+    match value {
+        serde_json::Value::Object(obj) => {
+            let is_gif = obj.get("id").and_then(serde_json::Value::as_str) == Some(gif_id);
+
+            if let Some(media_formats) = obj.get("media_formats").filter(|_| is_gif) {
+                return Some(media_formats);
+            }
+
+            obj.values().find_map(|x| find_media_formats(x, gif_id))
+        }
+        serde_json::Value::Array(arr) => arr.iter().find_map(|x| find_media_formats(x, gif_id)),
+        _ => None,
+    }
+}
@@ -1,6 +1,6 @@
 use std::{result::Result, sync::LazyLock};
 
-use http::StatusCode;
+use http::{header, HeaderMap, HeaderValue, StatusCode};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, trace};
@@ -16,15 +16,19 @@ pub struct Instagram;
 #[typetag::serde]
 impl Extractor for Instagram {
     fn description(&self) -> &'static str {
-        "Get images and videos from Instagram posts"
+        "Get images and videos from Instagram posts, stories and highlights"
     }
 
     async fn can_handle(&self, request: &ExtractInfoRequest) -> bool {
-        Self::is_post_url(&request.url)
+        Self::is_post_url(&request.url) || Self::is_story_url(&request.url)
     }
 
     async fn extract_info(&self, request: &ExtractInfoRequest) -> Result<ExtractedInfo, String> {
-        let media_urls = get_media_urls(request.url.as_str()).await?;
+        let media_urls = if Self::is_story_url(&request.url) {
+            get_story_media_urls(request).await?
+        } else {
+            get_media_urls(request).await?
+        };
 
         Ok(ExtractedInfo::from_urls(request, media_urls))
     }
@@ -35,10 +39,30 @@ static URL_MATCH: LazyLock<Regex> = LazyLock::new(|| {
         .expect("Invalid regex")
 });
 
+static HIGHLIGHT_URL_MATCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^https?://(www\.)?instagram\.com/stories/highlights/(?P<highlight_id>\d+)")
+        .expect("Invalid regex")
+});
+
+static STORY_URL_MATCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^https?://(www\.)?instagram\.com/stories/(?P<username>[A-Za-z0-9._]+)(/(?P<story_id>\d+))?")
+        .expect("Invalid regex")
+});
+
+const INSTAGRAM_APP_ID: &str = "936619743392459";
+
+const LOGIN_REQUIRED_ERROR: &str = "Login required. Instagram only shows this content to logged \
+                                    in users. Supply the cookies of a logged in account to \
+                                    access it.";
+
 impl Instagram {
     pub fn is_post_url(url: &Url) -> bool {
         URL_MATCH.is_match(url.as_str())
     }
+
+    pub fn is_story_url(url: &Url) -> bool {
+        STORY_URL_MATCH.is_match(url.as_str())
+    }
 }
 
 #[derive(Deserialize)]
@@ -85,7 +109,8 @@ impl XDTGraphEdges {
     }
 }
 
-async fn get_media_urls(url: &str) -> Result<Vec<String>, String> {
+async fn get_media_urls(request: &ExtractInfoRequest) -> Result<Vec<String>, String> {
+    let url = request.url.as_str();
     trace!("Fetching instagram media URLs for: {}", &url);
 
     let post_id = URL_MATCH
@@ -95,10 +120,15 @@ async fn get_media_urls(url: &str) -> Result<Vec<String>, String> {
         .ok_or_else(|| "URL is not a valid Instagram post".to_string())?;
     debug!("Instagram post ID: {:?}", &post_id);
 
-    get_api_response(post_id).await.map(|x| x.get_media_urls())
+    get_api_response(post_id, &request.headers)
+        .await
+        .map(|x| x.get_media_urls())
 }
 
-async fn get_api_response(post_id: &str) -> Result<InstagramXDTGraphMedia, String> {
+async fn get_api_response(
+    post_id: &str,
+    request_headers: &HeaderMap,
+) -> Result<InstagramXDTGraphMedia, String> {
     let query_variables = serde_json::json!({
         "shortcode": post_id,
         "fetch_tagged_user_count": null,
@@ -120,10 +150,19 @@ async fn get_api_response(post_id: &str) -> Result<InstagramXDTGraphMedia, Strin
     };
     trace!("GraphQL Variables: {:?}", &graphql_variables);
 
-    let resp = Client::base()?
+    let mut req = Client::base()?
         .post("https://www.instagram.com/graphql/query/")
-        .header("Content-Type", "application/x-www-form-urlencoded")
-        .header("X-CSRFToken", app_helpers::id::time_id())
+        .header("Content-Type", "application/x-www-form-urlencoded");
+
+    if let Some(cookie) = request_headers.get(header::COOKIE) {
+        req = req.header(header::COOKIE, cookie);
+    }
+
+    let resp = req
+        .header(
+            "X-CSRFToken",
+            csrf_token(request_headers).unwrap_or_else(app_helpers::id::time_id),
+        )
         .body(graphql_variables)
         .send()
         .await
@@ -150,6 +189,11 @@ async fn get_api_response(post_id: &str) -> Result<InstagramXDTGraphMedia, Strin
     trace!(?media, "Got media");
 
     if media.is_some_and(serde_json::Value::is_null) {
+        if !request_headers.contains_key(header::COOKIE) {
+            debug!("No media found. Post probably requires login.");
+            return Err(LOGIN_REQUIRED_ERROR.to_string());
+        }
+
         debug!("No media found. Post is probably age restricted.");
         return Err("No media found. Post is probably age restricted.".to_string());
     }
@@ -158,3 +202,200 @@ async fn get_api_response(post_id: &str) -> Result<InstagramXDTGraphMedia, Strin
         .and_then(|x| serde_json::from_value::<InstagramXDTGraphMedia>(x.clone()).ok())
         .ok_or_else(|| "Failed to parse media from response".to_string())
 }
+
+#[derive(Debug, Deserialize)]
+struct InstagramReelItem {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    video_versions: Vec<InstagramMediaCandidate>,
+    #[serde(default)]
+    image_versions2: Option<InstagramImageVersions>,
+}
+impl InstagramReelItem {
+    fn get_media_url(&self) -> Option<String> {
+        let best = |candidates: &[InstagramMediaCandidate]| {
+            candidates
+                .iter()
+                .max_by_key(|x| x.width * x.height)
+                .map(|x| x.url.clone())
+        };
+
+        best(&self.video_versions).or_else(|| {
+            self.image_versions2
+                .as_ref()
+                .and_then(|x| best(&x.candidates))
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct InstagramImageVersions {
+    #[serde(default)]
+    candidates: Vec<InstagramMediaCandidate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InstagramMediaCandidate {
+    url: String,
+    #[serde(default)]
+    width: u64,
+    #[serde(default)]
+    height: u64,
+}
+
+async fn get_story_media_urls(request: &ExtractInfoRequest) -> Result<Vec<String>, String> {
+    let url = request.url.as_str();
+    trace!("Fetching instagram story media URLs for: {}", &url);
+
+    let headers = logged_in_headers(&request.headers)?;
+
+    let (reel_id, story_id) = if let Some(highlight_id) = HIGHLIGHT_URL_MATCH
+        .captures(url)
+        .and_then(|x| x.name("highlight_id"))
+    {
+        (format!("highlight:{}", highlight_id.as_str()), None)
+    } else {
+        let captures = STORY_URL_MATCH
+            .captures(url)
+            .ok_or_else(|| "URL is not a valid Instagram story".to_string())?;
+        let username = captures
+            .name("username")
+            .map(|x| x.as_str())
+            .ok_or_else(|| "URL is not a valid Instagram story".to_string())?;
+        let story_id = captures.name("story_id").map(|x| x.as_str().to_string());
+
+        (get_user_id(username, &headers).await?, story_id)
+    };
+    debug!(?reel_id, ?story_id, "Instagram reel");
+
+    let items = get_reel_items(&reel_id, &headers).await?;
+    trace!(?items, "Got reel items");
+
+    let media_urls = items
+        .iter()
+        .filter(|x| {
+            story_id
+                .as_ref()
+                .is_none_or(|story_id| x.id.split('_').next() == Some(story_id.as_str()))
+        })
+        .filter_map(InstagramReelItem::get_media_url)
+        .collect::<Vec<_>>();
+
+    if media_urls.is_empty() {
+        return Err("No media found. The story has probably expired.".to_string());
+    }
+
+    Ok(media_urls)
+}
+
+async fn get_user_id(username: &str, headers: &HeaderMap) -> Result<String, String> {
+    let query = form_urlencoded::Serializer::new(String::new())
+        .append_pair("username", username)
+        .finish();
+
+    let resp = get_logged_in_json(
+        &format!("https://www.instagram.com/api/v1/users/web_profile_info/?{query}"),
+        headers,
+    )
+    .await?;
+
+    resp.get("data")
+        .and_then(|x| x.get("user"))
+        .and_then(|x| x.get("id"))
+        .and_then(serde_json::Value::as_str)
+        .map(ToString::to_string)
+        .ok_or_else(|| format!("Failed to find Instagram user {username:?}"))
+}
+
+async fn get_reel_items(
+    reel_id: &str,
+    headers: &HeaderMap,
+) -> Result<Vec<InstagramReelItem>, String> {
+    let reel_ids = form_urlencoded::Serializer::new(String::new())
+        .append_pair("reel_ids", reel_id)
+        .finish();
+
+    let resp = get_logged_in_json(
+        &format!("https://www.instagram.com/api/v1/feed/reels_media/?{reel_ids}"),
+        headers,
+    )
+    .await?;
+
+    let reel = resp
+        .get("reels")
+        .and_then(|x| x.get(reel_id))
+        .or_else(|| {
+            resp.get("reels_media")
+                .and_then(serde_json::Value::as_array)
+                .and_then(|x| x.first())
+        })
+        .ok_or_else(|| "No media found. The story has probably expired.".to_string())?;
+
+    reel.get("items")
+        .and_then(|x| serde_json::from_value::<Vec<InstagramReelItem>>(x.clone()).ok())
+        .ok_or_else(|| "Failed to parse story items from response".to_string())
+}
+
+async fn get_logged_in_json(url: &str, headers: &HeaderMap) -> Result<serde_json::Value, String> {
+    let resp = Client::base()?
+        .get(url)
+        .headers(headers.clone())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to instagram API: {e:?}"))?;
+
+    trace!(?resp, "Got response from instagram API");
+
+    if matches!(
+        resp.status(),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+    ) || resp.url().path().starts_with("/accounts/login")
+    {
+        return Err(LOGIN_REQUIRED_ERROR.to_string());
+    }
+
+    let resp = resp
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response from instagram API: {e:?}"))?;
+
+    trace!("Got response: {:?}", &resp);
+
+    let login_required = resp
+        .get("require_login")
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or_default()
+        || resp.get("message").and_then(serde_json::Value::as_str) == Some("login_required");
+
+    if login_required {
+        return Err(LOGIN_REQUIRED_ERROR.to_string());
+    }
+
+    Ok(resp)
+}
+
+fn logged_in_headers(request_headers: &HeaderMap) -> Result<HeaderMap, String> {
+    let cookie = request_headers
+        .get(header::COOKIE)
+        .ok_or_else(|| LOGIN_REQUIRED_ERROR.to_string())?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::COOKIE, cookie.clone());
+    headers.insert("X-IG-App-ID", HeaderValue::from_static(INSTAGRAM_APP_ID));
+
+    if let Some(csrf_token) = csrf_token(request_headers).and_then(|x| x.parse().ok()) {
+        headers.insert("X-CSRFToken", csrf_token);
+    }
+
+    Ok(headers)
+}
+
+fn csrf_token(request_headers: &HeaderMap) -> Option<String> {
+    request_headers
+        .get(header::COOKIE)
+        .and_then(|x| x.to_str().ok())?
+        .split(';')
+        .find_map(|x| x.trim().strip_prefix("csrftoken="))
+        .map(ToString::to_string)
+}
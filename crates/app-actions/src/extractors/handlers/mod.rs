@@ -1,10 +1,15 @@
 pub mod activity_pub;
 pub mod bsky;
 pub mod fallthough;
+pub mod gif_sites;
 pub mod imgur;
 pub mod instagram;
 pub mod music;
+pub mod ninegag;
+pub mod open_graph;
+pub mod pinterest;
 pub mod reddit;
+pub mod threads;
 pub mod tiktok;
 pub mod tumblr;
 pub mod twitter;
@@ -23,13 +28,20 @@ pub fn available_extractors() -> Vec<ExtractorEntry> {
     vec![
         Arc::new(imgur::Imgur),
         Arc::new(instagram::Instagram),
+        Arc::new(threads::Threads),
         Arc::new(reddit::Reddit),
         Arc::new(tiktok::Tiktok),
         Arc::new(tumblr::Tumblr),
         Arc::new(twitter::Twitter),
         Arc::new(music::Music),
         Arc::new(bsky::Bsky),
+        Arc::new(ninegag::NineGag),
+        Arc::new(pinterest::Pinterest),
+        Arc::new(gif_sites::giphy::Giphy),
+        Arc::new(gif_sites::tenor::Tenor),
+        Arc::new(gif_sites::imgflip::Imgflip),
         Arc::new(activity_pub::ActivityPub),
+        Arc::new(open_graph::OpenGraph),
         Arc::new(fallthough::Fallthrough),
     ]
 }
@@ -0,0 +1,117 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+use url::Url;
+
+use super::{ExtractInfoRequest, ExtractedInfo, Extractor};
+use crate::{common::request::Client, downloaders::handlers::generic::Generic};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NineGag;
+
+#[async_trait::async_trait]
+#[typetag::serde]
+impl Extractor for NineGag {
+    fn description(&self) -> &'static str {
+        "Get the highest quality image or video from 9GAG posts"
+    }
+
+    async fn can_handle(&self, request: &ExtractInfoRequest) -> bool {
+        Self::is_post_url(&request.url)
+    }
+
+    async fn extract_info(&self, request: &ExtractInfoRequest) -> Result<ExtractedInfo, String> {
+        let post_id = URL_MATCH
+            .captures(request.url.as_str())
+            .and_then(|x| x.name("post_id"))
+            .map(|x| x.as_str())
+            .ok_or_else(|| "URL is not a valid 9GAG post".to_string())?;
+        debug!(?post_id, "Got 9GAG post ID");
+
+        let post = get_post(post_id).await?;
+        trace!(?post, "Got 9GAG post");
+
+        let media_url = post
+            .get_media_url()
+            .ok_or_else(|| "Failed to find media in 9GAG post".to_string())?;
+
+        Ok(ExtractedInfo::from_url(request, media_url.as_str())
+            .with_preferred_downloader(Some(Generic)))
+    }
+}
+
+static URL_MATCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^https?://(www\.|m\.)?9gag\.com/gag/(?P<post_id>[a-zA-Z0-9]+)")
+        .expect("Invalid regex")
+});
+
+impl NineGag {
+    #[must_use]
+    pub fn is_post_url(url: &Url) -> bool {
+        URL_MATCH.is_match(url.as_str())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NineGagPost {
+    #[serde(rename = "type")]
+    post_type: String,
+    images: HashMap<String, NineGagImage>,
+}
+impl NineGagPost {
+    fn get_media_url(&self) -> Option<String> {
+        if self.post_type == "Animated" {
+            // The H.264 version plays everywhere, the AV1 one is only used if it's missing
+            let video = self.images.get("image460sv")?;
+
+            return video
+                .url
+                .clone()
+                .filter(|x| !x.is_empty())
+                .or_else(|| video.av1_url.clone());
+        }
+
+        self.images
+            .iter()
+            .filter(|(k, _)| !k.contains("Thumbnail"))
+            .filter(|(_, v)| v.duration.is_none())
+            .max_by_key(|(_, v)| v.width * v.height)
+            .and_then(|(_, v)| v.url.clone())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NineGagImage {
+    url: Option<String>,
+    #[serde(default)]
+    width: u64,
+    #[serde(default)]
+    height: u64,
+    av1_url: Option<String>,
+    duration: Option<u64>,
+}
+
+async fn get_post(post_id: &str) -> Result<NineGagPost, String> {
+    let resp = Client::base()?
+        .get("https://9gag.com/v1/post")
+        .query(&[("id", post_id)])
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to 9GAG: {e:?}"))?
+        .error_for_status()
+        .map_err(|e| format!("Failed to get 9GAG post: {e:?}"))?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response from 9GAG: {e:?}"))?;
+
+    trace!(?resp, "Got response from 9GAG");
+
+    resp.get("data")
+        .and_then(|x| x.get("post"))
+        .and_then(|x| serde_json::from_value::<NineGagPost>(x.clone()).ok())
+        .ok_or_else(|| "Failed to parse post from 9GAG response".to_string())
+}
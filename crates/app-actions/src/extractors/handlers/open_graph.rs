@@ -0,0 +1,251 @@
+use app_helpers::file_type::mime;
+use http::header;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+use url::Url;
+
+use super::{fallthough::Fallthrough, ExtractInfoRequest, ExtractedInfo, Extractor};
+use crate::{
+    common::{request::Client, url::validate_public_url},
+    downloaders::handlers::generic::Generic,
+    extractors::ExtractedUrlInfo,
+};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct OpenGraph;
+
+#[async_trait::async_trait]
+#[typetag::serde]
+impl Extractor for OpenGraph {
+    fn description(&self) -> &'static str {
+        "Get media from the OpenGraph/Twitter card tags and oEmbed links of a page. If nothing is \
+         found or the page only embeds a player the URL is forwarded as is."
+    }
+
+    async fn can_handle(&self, request: &ExtractInfoRequest) -> bool {
+        matches!(request.url.scheme(), "http" | "https")
+    }
+
+    async fn extract_info(&self, request: &ExtractInfoRequest) -> Result<ExtractedInfo, String> {
+        let resp = request
+            .as_request_builder()?
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch page: {e:?}"))?;
+        trace!(?resp, "Got page response");
+
+        let content_type = resp
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.parse::<mime::Mime>().ok());
+        debug!(?content_type, "Got page content type");
+
+        match content_type.as_ref().map(mime::Mime::type_) {
+            Some(mime::IMAGE | mime::VIDEO | mime::AUDIO) => {
+                debug!("URL is a media file");
+
+                return Ok(ExtractedInfo::from_url(request, request.url.as_str())
+                    .with_preferred_downloader(Some(Generic)));
+            }
+            Some(mime::TEXT)
+                if content_type.as_ref().map(mime::Mime::subtype) == Some(mime::HTML) => {}
+            _ => return Fallthrough.extract_info(request).await,
+        }
+
+        let page_url = resp.url().clone();
+        let body = resp
+            .text()
+            .await
+            .map_err(|e| format!("Failed to get page body: {e:?}"))?;
+
+        let page_media =
+            tokio::task::spawn_blocking(move || PageMedia::from_html(&body, &page_url))
+                .await
+                .map_err(|e| format!("Failed to parse page: {e:?}"))??;
+        trace!(?page_media, "Got media from page");
+
+        let mut urls = public_urls(page_media.media_urls()).await;
+
+        if urls.is_empty() {
+            if let Some(oembed_url) = &page_media.oembed_url {
+                match get_oembed_photo(oembed_url).await {
+                    Ok(Some(x)) => urls.push(
+                        ExtractedUrlInfo::new(x.as_str()).with_preferred_downloader(Some(Generic)),
+                    ),
+                    Ok(None) => {}
+                    Err(e) => debug!(?e, "Failed to get oEmbed data"),
+                }
+            }
+        }
+
+        // The image of a page with an embedded player is only a thumbnail of the actual content
+        if urls.is_empty() && !page_media.has_embeds() {
+            urls = public_urls(page_media.image_urls()).await;
+        }
+
+        if urls.is_empty() {
+            debug!("No media files found on page");
+            return Fallthrough.extract_info(request).await;
+        }
+
+        Ok(ExtractedInfo::from_urls(request, urls))
+    }
+}
+
+#[derive(Debug, Default)]
+struct PageMedia {
+    streams: Vec<Url>,
+    videos: Vec<PageVideo>,
+    audios: Vec<Url>,
+    images: Vec<Url>,
+    players: Vec<Url>,
+    oembed_url: Option<Url>,
+}
+
+#[derive(Debug)]
+struct PageVideo {
+    url: Url,
+    is_file: bool,
+}
+
+impl PageMedia {
+    fn from_html(html: &str, page_url: &Url) -> Result<Self, String> {
+        let dom = tl::parse(html, tl::ParserOptions::default())
+            .map_err(|e| format!("Failed to parse html: {:?}", e))?;
+        let parser = dom.parser();
+
+        let attr = |tag: &tl::HTMLTag, name: &str| {
+            tag.attributes()
+                .get(name)
+                .flatten()
+                .map(|x| x.as_utf8_str().trim().to_string())
+        };
+
+        let mut media = Self::default();
+        let mut video_type = None;
+
+        for tag in dom
+            .query_selector("meta")
+            .expect("Failed parse query selector")
+            .filter_map(|x| x.get(parser))
+            .filter_map(|x| x.as_tag())
+        {
+            let Some(key) = attr(tag, "property").or_else(|| attr(tag, "name")) else {
+                continue;
+            };
+            let Some(content) = attr(tag, "content").filter(|x| !x.is_empty()) else {
+                continue;
+            };
+
+            let url = || page_url.join(&content).ok();
+
+            match key.as_str() {
+                "twitter:player:stream" => media.streams.extend(url()),
+                "og:video" | "og:video:url" | "og:video:secure_url" => {
+                    media.videos.extend(url().map(|url| PageVideo {
+                        url,
+                        is_file: false,
+                    }));
+                }
+                "og:video:type" => video_type = Some(content),
+                "og:audio" | "og:audio:url" | "og:audio:secure_url" => media.audios.extend(url()),
+                "og:image" | "og:image:url" | "og:image:secure_url" | "twitter:image" => {
+                    media.images.extend(url());
+                }
+                "twitter:player" => media.players.extend(url()),
+                _ => {}
+            }
+        }
+
+        // Embeds (eg. `text/html` players) are left for yt-dlp to handle
+        let video_is_file = video_type.is_some_and(|x| x.starts_with("video/"));
+        for video in &mut media.videos {
+            video.is_file = video_is_file;
+        }
+
+        media.oembed_url = dom
+            .query_selector("link")
+            .expect("Failed parse query selector")
+            .filter_map(|x| x.get(parser))
+            .filter_map(|x| x.as_tag())
+            .filter(|x| attr(x, "type").as_deref() == Some("application/json+oembed"))
+            .find_map(|x| attr(x, "href"))
+            .and_then(|x| page_url.join(&x).ok());
+
+        Ok(media)
+    }
+
+    fn media_urls(&self) -> Vec<ExtractedUrlInfo> {
+        let streams =
+            self.streams.iter().chain(&self.audios).map(|x| {
+                ExtractedUrlInfo::new(x.as_str()).with_preferred_downloader(Some(Generic))
+            });
+
+        let videos = self.videos.iter().filter(|x| x.is_file).map(|x| {
+            ExtractedUrlInfo::new(x.url.as_str()).with_preferred_downloader(Some(Generic))
+        });
+
+        streams.chain(videos).collect()
+    }
+
+    fn image_urls(&self) -> Vec<ExtractedUrlInfo> {
+        self.images
+            .iter()
+            .map(|x| ExtractedUrlInfo::new(x.as_str()).with_preferred_downloader(Some(Generic)))
+            .collect()
+    }
+
+    /// Whether the page embeds a player instead of linking the media file
+    fn has_embeds(&self) -> bool {
+        !self.players.is_empty() || self.videos.iter().any(|x| !x.is_file)
+    }
+}
+
+/// The page decides where these point to so make sure nothing internal gets fetched
+async fn public_urls(urls: Vec<ExtractedUrlInfo>) -> Vec<ExtractedUrlInfo> {
+    let mut public = vec![];
+    for info in urls {
+        match validate_public_url(info.url.url().as_str()).await {
+            Ok(_) => public.push(info),
+            Err(e) => debug!(?e, "Skipping page media"),
+        }
+    }
+
+    public
+}
+
+#[derive(Debug, Deserialize)]
+struct OEmbedResponse {
+    #[serde(rename = "type")]
+    oembed_type: String,
+    url: Option<String>,
+}
+
+/// Only `photo` type oEmbeds point to a media file,
+/// the other types contain HTML for embedding the content.
+async fn get_oembed_photo(oembed_url: &Url) -> Result<Option<Url>, String> {
+    validate_public_url(oembed_url.as_str()).await?;
+
+    let resp = Client::base()?
+        .get(oembed_url.as_str())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send oEmbed request: {e:?}"))?
+        .error_for_status()
+        .map_err(|e| format!("Failed to get oEmbed data: {e:?}"))?
+        .json::<OEmbedResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse oEmbed data: {e:?}"))?;
+    trace!(?resp, "Got oEmbed data");
+
+    if resp.oembed_type != "photo" {
+        return Ok(None);
+    }
+
+    let Some(photo_url) = resp.url.and_then(|x| oembed_url.join(&x).ok()) else {
+        return Ok(None);
+    };
+
+    validate_public_url(photo_url.as_str()).await.map(Some)
+}
@@ -0,0 +1,168 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+use url::Url;
+
+use super::{ExtractInfoRequest, ExtractedInfo, Extractor};
+use crate::{
+    common::request::Client,
+    downloaders::handlers::{generic::Generic, segmented::Segmented},
+    extractors::{common::short_link::resolve_short_link, ExtractedUrlInfo},
+};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Pinterest;
+
+#[async_trait::async_trait]
+#[typetag::serde]
+impl Extractor for Pinterest {
+    fn description(&self) -> &'static str {
+        "Get the original image or video from Pinterest pins"
+    }
+
+    async fn can_handle(&self, request: &ExtractInfoRequest) -> bool {
+        Self::is_pin_url(&request.url) || Self::is_short_url(&request.url)
+    }
+
+    async fn extract_info(&self, request: &ExtractInfoRequest) -> Result<ExtractedInfo, String> {
+        let pin_url = if Self::is_short_url(&request.url) {
+            resolve_short_link(request).await?.url
+        } else {
+            request.url.clone()
+        };
+
+        let pin_id = PIN_PATH_MATCH
+            .captures(pin_url.path())
+            .and_then(|x| x.name("pin_id"))
+            .map(|x| x.as_str())
+            .ok_or_else(|| format!("URL {:?} is not a valid Pinterest pin", pin_url.as_str()))?;
+        debug!(?pin_id, "Got pinterest pin ID");
+
+        let pin = get_pin_data(pin_id).await?;
+        trace!(?pin, "Got pinterest pin data");
+
+        let urls = get_pin_media(&pin);
+
+        if urls.is_empty() {
+            return Err("Failed to find media in Pinterest pin".to_string());
+        }
+
+        Ok(ExtractedInfo::from_urls(request, urls))
+    }
+}
+
+/// `pinterest.com` and the country domains (eg. `pinterest.de`, `pinterest.co.uk`)
+/// along with their subdomains
+static PIN_DOMAIN_MATCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([a-z0-9-]+\.)*pinterest\.(com|[a-z]{2}|com?\.[a-z]{2})$").expect("Invalid regex")
+});
+
+static PIN_PATH_MATCH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^/pin/(?P<pin_id>\d+)").expect("Invalid regex"));
+
+impl Pinterest {
+    #[must_use]
+    pub fn is_pin_url(url: &Url) -> bool {
+        url.domain().is_some_and(|x| PIN_DOMAIN_MATCH.is_match(x))
+            && PIN_PATH_MATCH.is_match(url.path())
+    }
+
+    #[must_use]
+    pub fn is_short_url(url: &Url) -> bool {
+        url.domain().is_some_and(|x| x == "pin.it") && url.path().len() > 1
+    }
+}
+
+async fn get_pin_data(pin_id: &str) -> Result<serde_json::Value, String> {
+    let data = serde_json::json!({
+        "options": {
+            "id": pin_id,
+            "field_set_key": "unauth_react_main_pin",
+        },
+    });
+
+    let resp = Client::base()?
+        .get("https://www.pinterest.com/resource/PinResource/get/")
+        .query(&[("data", data.to_string())])
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to pinterest: {e:?}"))?
+        .error_for_status()
+        .map_err(|e| format!("Failed to get pinterest pin: {e:?}"))?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse response from pinterest: {e:?}"))?;
+
+    resp.get("resource_response")
+        .and_then(|x| x.get("data"))
+        .filter(|x| x.is_object())
+        .cloned()
+        .ok_or_else(|| "Failed to get pin data from pinterest response".to_string())
+}
+
+/// Videos are in `videos` for regular pins and in the story pages for idea pins.
+/// Images use the `orig` size which is the originally uploaded file.
+fn get_pin_media(pin: &serde_json::Value) -> Vec<ExtractedUrlInfo> {
+    let video_lists = std::iter::once(pin.get("videos").and_then(|x| x.get("video_list")))
+        .chain(
+            pin.get("story_pin_data")
+                .and_then(|x| x.get("pages"))
+                .and_then(serde_json::Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|x| x.get("blocks").and_then(serde_json::Value::as_array))
+                .flatten()
+                .map(|x| x.get("video").and_then(|x| x.get("video_list"))),
+        )
+        .flatten()
+        .filter_map(get_best_video)
+        .collect::<Vec<_>>();
+
+    if !video_lists.is_empty() {
+        return video_lists;
+    }
+
+    pin.get("images")
+        .and_then(|x| x.get("orig"))
+        .and_then(|x| x.get("url"))
+        .and_then(serde_json::Value::as_str)
+        .map(|x| ExtractedUrlInfo::new(x).with_preferred_downloader(Some(Generic)))
+        .into_iter()
+        .collect()
+}
+
+fn get_best_video(video_list: &serde_json::Value) -> Option<ExtractedUrlInfo> {
+    let video_url = |key: &str| {
+        video_list
+            .get(key)
+            .and_then(|x| x.get("url"))
+            .and_then(serde_json::Value::as_str)
+    };
+
+    if let Some(hls_url) = ["V_HLSV4", "V_HLSV3_WEB", "V_HLSV3_MOBILE"]
+        .into_iter()
+        .find_map(video_url)
+    {
+        return Some(ExtractedUrlInfo::new(hls_url).with_preferred_downloader(Some(Segmented)));
+    }
+
+    video_list
+        .as_object()?
+        .values()
+        .filter(|x| {
+            x.get("url")
+                .and_then(serde_json::Value::as_str)
+                .and_then(|x| std::path::Path::new(x).extension())
+                .is_some_and(|x| x.eq_ignore_ascii_case("mp4"))
+        })
+        .max_by_key(|x| {
+            let dimension = |key: &str| x.get(key).and_then(serde_json::Value::as_u64);
+
+            dimension("width").unwrap_or_default() * dimension("height").unwrap_or_default()
+        })
+        .and_then(|x| x.get("url"))
+        .and_then(serde_json::Value::as_str)
+        .map(|x| ExtractedUrlInfo::new(x).with_preferred_downloader(Some(Generic)))
+}
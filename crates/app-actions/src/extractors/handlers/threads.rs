@@ -0,0 +1,212 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+use url::Url;
+
+use super::{twitter::Twitter, ExtractInfoRequest, ExtractedInfo, Extractor};
+use crate::{
+    downloaders::handlers::generic::Generic,
+    extractors::{ExtractedUrlInfo, ExtractorOptions},
+};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Threads;
+
+#[async_trait::async_trait]
+#[typetag::serde]
+impl Extractor for Threads {
+    fn description(&self) -> &'static str {
+        "Downloads images and videos from Threads posts and screenshots the post itself."
+    }
+
+    async fn can_handle(&self, request: &ExtractInfoRequest) -> bool {
+        Self::is_post_url(&request.url)
+    }
+
+    async fn extract_info(&self, request: &ExtractInfoRequest) -> Result<ExtractedInfo, String> {
+        let options = request
+            .extractor_options::<ThreadsExtractorOptions>()
+            .unwrap_or_default();
+
+        trace!(?options, "Using extractor options");
+
+        let mut urls = get_media_urls(request)
+            .await
+            .map_err(|e| format!("Failed to get threads media urls: {e}"))?
+            .into_iter()
+            .map(|x| ExtractedUrlInfo::new(x.as_str()).with_preferred_downloader(Some(Generic)))
+            .collect::<Vec<_>>();
+
+        if options.screenshot {
+            urls.push(Twitter.screenshot_tweet_url_info(request.url.as_str()));
+        }
+
+        Ok(ExtractedInfo::from_urls(request, urls))
+    }
+}
+
+static URL_MATCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^https?://(www\.)?threads\.(net|com)/@(?P<username>[^/?]+)/post/(?P<post_id>[^/?]+)",
+    )
+    .expect("Invalid regex")
+});
+
+impl Threads {
+    #[must_use]
+    pub fn is_post_url(url: &Url) -> bool {
+        URL_MATCH.is_match(url.as_str())
+    }
+
+    #[must_use]
+    pub fn options() -> ThreadsExtractorOptions {
+        ThreadsExtractorOptions::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct ThreadsExtractorOptions {
+    /// Also screenshot the post using the screenshot endpoint
+    screenshot: bool,
+}
+impl Default for ThreadsExtractorOptions {
+    fn default() -> Self {
+        Self { screenshot: true }
+    }
+}
+impl ThreadsExtractorOptions {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub const fn with_screenshot(mut self, screenshot: bool) -> Self {
+        self.screenshot = screenshot;
+        self
+    }
+}
+impl From<ThreadsExtractorOptions> for ExtractorOptions {
+    fn from(val: ThreadsExtractorOptions) -> Self {
+        let val = serde_json::to_value(val)
+            .ok()
+            .and_then(|x| x.as_object().cloned())
+            .expect("Failed to serialize options");
+
+        val.into_iter().collect()
+    }
+}
+
+async fn get_media_urls(request: &ExtractInfoRequest) -> Result<Vec<String>, String> {
+    let post_id = URL_MATCH
+        .captures(request.url.as_str())
+        .and_then(|x| x.name("post_id"))
+        .map(|x| x.as_str().to_string())
+        .ok_or_else(|| "URL is not a valid Threads post".to_string())?;
+    debug!(?post_id, "Threads post ID");
+
+    let resp = request
+        .as_request_builder()?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to threads: {:?}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to get text from threads response: {:?}", e))?;
+
+    trace!("Got response from threads");
+
+    let script_data = tokio::task::spawn_blocking(move || {
+        let dom = tl::parse(&resp, tl::ParserOptions::default())
+            .map_err(|e| format!("Failed to parse html from threads: {:?}", e))?;
+        let parser = dom.parser();
+
+        trace!("Parsed html from threads");
+
+        Ok::<_, String>(
+            dom.query_selector(r#"script[type="application/json"]"#)
+                .expect("Failed parse query selector")
+                .filter_map(|x| x.get(parser))
+                .filter_map(|x| x.as_tag())
+                .map(|x| x.inner_text(parser).to_string())
+                .filter(|x| x.contains("thread_items"))
+                .filter_map(|x| serde_json::from_str::<serde_json::Value>(&x).ok())
+                .collect::<Vec<_>>(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to get script data from threads: {:?}", e))??;
+
+    trace!(count = script_data.len(), "Got script data from threads");
+
+    let mut posts = vec![];
+    for data in &script_data {
+        find_thread_posts(data, &mut posts);
+    }
+
+    let post = posts
+        .iter()
+        .find(|x| x.get("code").and_then(serde_json::Value::as_str) == Some(post_id.as_str()))
+        .ok_or_else(|| format!("Failed to find data for post {post_id:?} in page"))?;
+
+    trace!(?post, "Got post data");
+
+    let media_urls = post
+        .get("carousel_media")
+        .and_then(serde_json::Value::as_array)
+        .map_or_else(
+            || get_item_media_url(post).into_iter().collect::<Vec<_>>(),
+            |items| items.iter().filter_map(get_item_media_url).collect(),
+        );
+
+    debug!(?media_urls, "Got threads media urls");
+
+    Ok(media_urls)
+}
+
+/// The page data is deeply nested so just look for every `thread_items` list in it.
+fn find_thread_posts<'a>(value: &'a serde_json::Value, posts: &mut Vec<&'a serde_json::Value>) {
+    match value {
+        serde_json::Value::Object(obj) => {
+            if let Some(items) = obj.get("thread_items").and_then(|x| x.as_array()) {
+                posts.extend(items.iter().filter_map(|x| x.get("post")));
+            }
+
+            for v in obj.values() {
+                find_thread_posts(v, posts);
+            }
+        }
+        serde_json::Value::Array(arr) => {
+            for v in arr {
+                find_thread_posts(v, posts);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn get_item_media_url(item: &serde_json::Value) -> Option<String> {
+    let best_candidate = |candidates: Option<&serde_json::Value>| {
+        candidates
+            .and_then(serde_json::Value::as_array)?
+            .iter()
+            .max_by_key(|x| {
+                let dimension = |key: &str| x.get(key).and_then(serde_json::Value::as_u64);
+
+                dimension("width").unwrap_or_default() * dimension("height").unwrap_or_default()
+            })
+            .and_then(|x| x.get("url"))
+            .and_then(serde_json::Value::as_str)
+            .map(ToString::to_string)
+    };
+
+    best_candidate(item.get("video_versions")).or_else(|| {
+        best_candidate(
+            item.get("image_versions2")
+                .and_then(|x| x.get("candidates")),
+        )
+    })
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, trace};
 use url::Url;
@@ -8,6 +9,10 @@ use super::{ExtractInfoRequest, ExtractedInfo, Extractor};
 use crate::{
     common::{request::USER_AGENT, url::UrlWithMeta},
     downloaders::handlers::generic::Generic,
+    extractors::{
+        common::short_link::resolve_short_link, PostEngagement, PostMetadata,
+        RENDER_SLIDESHOW_META_KEY,
+    },
 };
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -17,7 +22,7 @@ pub struct Tiktok;
 #[typetag::serde]
 impl Extractor for Tiktok {
     fn description(&self) -> &'static str {
-        "Get videos from TikTok posts"
+        "Get videos and photo slideshows from TikTok posts"
     }
 
     async fn can_handle(&self, request: &ExtractInfoRequest) -> bool {
@@ -25,22 +30,63 @@ impl Extractor for Tiktok {
     }
 
     async fn extract_info(&self, request: &ExtractInfoRequest) -> Result<ExtractedInfo, String> {
-        let media_urls = get_media_download_urls(request)
+        let post_request = if Self::is_short_url(&request.url) {
+            resolve_short_link(request).await?
+        } else {
+            request.clone()
+        };
+
+        let (media_urls, post_metadata) = get_media_download_urls(&post_request)
             .await
             .map_err(|e| format!("Failed to get media download urls for tiktok post: {:?}", e))?;
 
-        Ok(ExtractedInfo::from_url(request, media_urls).with_preferred_downloader(Some(Generic)))
+        let options = request
+            .extractor_options::<TiktokExtractorOptions>()
+            .unwrap_or_default();
+        trace!(?options, "Using extractor options");
+
+        let is_slideshow = media_urls.len() > 1;
+
+        Ok(ExtractedInfo::from_urls(request, media_urls)
+            .with_preferred_downloader(Some(Generic))
+            .with_meta("slideshow", is_slideshow)
+            .with_meta(
+                RENDER_SLIDESHOW_META_KEY,
+                is_slideshow && options.render_slideshow,
+            )
+            .with_post_metadata(Some(post_metadata)))
     }
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct TiktokExtractorOptions {
+    /// Also render photo slideshows into a video with the sound
+    pub render_slideshow: bool,
+}
+
 impl Tiktok {
     #[must_use]
     pub fn is_post_url(url: &Url) -> bool {
-        url.host_str().is_some_and(|x| x == "www.tiktok.com") && url.path().starts_with("/@")
+        match url.host_str() {
+            Some("www.tiktok.com" | "tiktok.com") => url.path().starts_with("/@"),
+            Some(_) => Self::is_short_url(url),
+            None => false,
+        }
+    }
+
+    /// Short links (eg. `https://vm.tiktok.com/ZMabcdef/`) redirect to the full post URL
+    #[must_use]
+    pub fn is_short_url(url: &Url) -> bool {
+        url.host_str()
+            .is_some_and(|x| matches!(x, "vm.tiktok.com" | "vt.tiktok.com"))
+            && url.path().len() > 1
     }
 }
 
-async fn get_media_download_urls(req: &ExtractInfoRequest) -> Result<UrlWithMeta, String> {
+async fn get_media_download_urls(
+    req: &ExtractInfoRequest,
+) -> Result<(Vec<UrlWithMeta>, PostMetadata), String> {
     debug!("Getting media download urls for tiktok post");
 
     let resp = req
@@ -50,6 +96,9 @@ async fn get_media_download_urls(req: &ExtractInfoRequest) -> Result<UrlWithMeta
         .map_err(|e| format!("Failed to send request to tiktok: {:?}", e))?;
     trace!(?resp, "Got response from tiktok");
 
+    let post_url = resp.url().clone();
+    debug!(post_url = ?post_url.as_str(), "Resolved tiktok post URL");
+
     let mut resp_cookies = HashMap::<String, String>::new();
     for cookie in resp.cookies() {
         resp_cookies.insert(cookie.name().to_string(), cookie.value().to_string());
@@ -107,17 +156,118 @@ async fn get_media_download_urls(req: &ExtractInfoRequest) -> Result<UrlWithMeta
         .ok_or_else(|| "Failed to get video data from post data".to_string())?;
     trace!(?video_data, "Got video data from post data");
 
-    let video_url = video_data
-        .get("video")
-        .and_then(|x| x.get("playAddr"))
-        .and_then(|x| x.as_str())
-        .ok_or_else(|| "Failed to get video url from video data".to_string())?;
-    trace!(?video_url, "Got video url from video data");
+    let media_urls = match get_slideshow_urls(video_data) {
+        Some(x) => x,
+        None => {
+            let video_url = video_data
+                .get("video")
+                .and_then(|x| x.get("playAddr"))
+                .and_then(|x| x.as_str())
+                .ok_or_else(|| "Failed to get video url from video data".to_string())?;
+            trace!(?video_url, "Got video url from video data");
+
+            vec![video_url.to_string()]
+        }
+    };
+
+    let download_infos = media_urls
+        .into_iter()
+        .map(|x| {
+            UrlWithMeta::from_url(&x)
+                .with_header("User-Agent", &USER_AGENT)
+                .with_header("Referer", &post_url)
+                .with_header("Cookie", &format!("tt_chain_token={}", csrf_token))
+        })
+        .collect();
+
+    let post_metadata = get_post_metadata(video_data, &post_url);
+    trace!(?post_metadata, "Got post metadata from video data");
+
+    Ok((download_infos, post_metadata))
+}
+
+fn get_post_metadata(video_data: &serde_json::Value, post_url: &Url) -> PostMetadata {
+    let author_str = |key: &str| {
+        video_data
+            .get("author")
+            .and_then(|x| x.get(key))
+            .and_then(serde_json::Value::as_str)
+    };
+
+    // Numbers are sometimes sent as strings
+    let as_u64 = |x: &serde_json::Value| {
+        x.as_u64()
+            .or_else(|| x.as_str().and_then(|x| x.parse().ok()))
+    };
+
+    let stat = |key: &str| {
+        video_data
+            .get("stats")
+            .and_then(|x| x.get(key))
+            .and_then(as_u64)
+    };
+
+    let created_at = video_data
+        .get("createTime")
+        .and_then(as_u64)
+        .and_then(|x| i64::try_from(x).ok())
+        .and_then(|x| DateTime::<Utc>::from_timestamp(x, 0));
+
+    PostMetadata::new()
+        .with_author_handle(author_str("uniqueId"))
+        .with_author_display_name(author_str("nickname"))
+        .with_text(
+            video_data
+                .get("desc")
+                .and_then(serde_json::Value::as_str)
+                .filter(|x| !x.is_empty()),
+        )
+        .with_created_at(created_at)
+        .with_source_url(Some(post_url.as_str()))
+        .with_engagement(PostEngagement {
+            likes: stat("diggCount"),
+            reposts: stat("shareCount"),
+            replies: stat("commentCount"),
+            quotes: None,
+            views: stat("playCount"),
+        })
+}
 
-    let download_info = UrlWithMeta::from_url(video_url)
-        .with_header("User-Agent", &USER_AGENT)
-        .with_header("Referer", &req.url)
-        .with_header("Cookie", &format!("tt_chain_token={}", csrf_token));
+/// Photo mode posts have a list of images and a sound instead of a video.
+/// Returns the images in order followed by the sound.
+fn get_slideshow_urls(video_data: &serde_json::Value) -> Option<Vec<String>> {
+    let images = video_data
+        .get("imagePost")
+        .and_then(|x| x.get("images"))
+        .and_then(serde_json::Value::as_array)?;
+
+    let mut urls = images
+        .iter()
+        .filter_map(|x| {
+            x.get("imageURL")
+                .and_then(|x| x.get("urlList"))
+                .and_then(serde_json::Value::as_array)
+                .and_then(|x| x.first())
+                .and_then(serde_json::Value::as_str)
+                .map(ToString::to_string)
+        })
+        .collect::<Vec<_>>();
+    trace!(?urls, "Got slideshow image urls from video data");
+
+    if urls.is_empty() {
+        return None;
+    }
+
+    let audio_url = video_data
+        .get("music")
+        .and_then(|x| x.get("playUrl"))
+        .and_then(serde_json::Value::as_str)
+        .filter(|x| !x.is_empty());
+    trace!(?audio_url, "Got slideshow audio url from video data");
+
+    if let Some(audio_url) = audio_url {
+        urls.push(audio_url.to_string());
+    }
 
-    Ok(download_info)
+    Some(urls)
 }
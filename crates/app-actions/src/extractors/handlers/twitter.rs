@@ -1,22 +1,25 @@
 use std::{string::ToString, sync::LazyLock};
 
 use app_config::timeframe::Timeframe;
+use chrono::{DateTime, Utc};
 use http::{header, HeaderMap};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 use url::{form_urlencoded, Url};
 
 use super::{ExtractInfoRequest, ExtractedInfo, Extractor};
 use crate::{
-    common::request::Client, config::ActionsConfig, downloaders::handlers::generic::Generic,
-    extractors::ExtractedUrlInfo,
+    common::request::Client,
+    config::ActionsConfig,
+    downloaders::handlers::generic::Generic,
+    extractors::{ExtractedUrlInfo, ExtractorOptions, PostEngagement, PostMetadata},
 };
 
 pub static URL_MATCH: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(
-        r"^https?://(www\.)?(twitter|x)\.com/(?P<username>[^/]+)/status/(?P<status_id>[0-9]+)",
+        r"^https?://(www\.|mobile\.)?(twitter|x|vxtwitter|fxtwitter|fixupx|fixvx)\.com/(?P<username>[^/]+)/status/(?P<status_id>[0-9]+)",
     )
     .expect("Invalid regex")
 });
@@ -62,26 +65,45 @@ impl Extractor for Twitter {
 
         trace!(?tweet_info, "Got tweet info");
 
-        let tweet_data = get_tweet_data(&tweet_info.status_id).await?;
+        let options = request
+            .extractor_options::<TwitterExtractorOptions>()
+            .unwrap_or_default();
+
+        trace!(?options, "Using extractor options");
+
+        let guest_auth = get_guest_auth().await?;
+
+        let tweet_data = get_tweet_data(&guest_auth, &tweet_info.status_id).await?;
 
         trace!(?tweet_data, "Got tweet data");
 
-        let mut tweet_media = get_tweet_media_urls(&tweet_data)
-            .unwrap_or_default()
-            .into_iter()
-            .map(Into::into)
+        let post_metadata = tweet_data.post_metadata(request.url.as_str());
+
+        trace!(?post_metadata, "Got tweet metadata");
+
+        let tweets = if options.include_thread {
+            get_thread_tweets(&guest_auth, tweet_data, options.max_thread_length()).await
+        } else {
+            vec![tweet_data]
+        };
+
+        trace!(count = tweets.len(), "Got tweets to extract");
+
+        let mut tweet_media = tweets
+            .iter()
+            .flat_map(|tweet| self.tweet_urls(tweet, &tweet_info.username, &options))
             .collect::<Vec<ExtractedUrlInfo>>();
 
         trace!(?tweet_media, "Got tweet media");
 
-        if tweet_info.username != "i" {
+        if !options.screenshot_each_tweet && tweet_info.username != "i" {
             let tweet_screenshot_url = self.screenshot_tweet_url_info(request.url.as_str());
 
             trace!("Adding Tweet screenshot URL: {:?}", &tweet_screenshot_url);
             tweet_media.push(tweet_screenshot_url);
         }
 
-        Ok(ExtractedInfo::from_urls(request, tweet_media))
+        Ok(ExtractedInfo::from_urls(request, tweet_media).with_post_metadata(Some(post_metadata)))
     }
 }
 
@@ -113,6 +135,111 @@ impl Twitter {
     pub fn is_media_url(url: &str) -> bool {
         MEDIA_URL_MATCH.is_match(url)
     }
+
+    #[must_use]
+    pub fn options() -> TwitterExtractorOptions {
+        TwitterExtractorOptions::default()
+    }
+
+    fn tweet_urls(
+        &self,
+        tweet: &TweetData,
+        fallback_username: &str,
+        options: &TwitterExtractorOptions,
+    ) -> Vec<ExtractedUrlInfo> {
+        let mut urls = get_tweet_media_urls(tweet)
+            .unwrap_or_default()
+            .into_iter()
+            .map(Into::into)
+            .collect::<Vec<ExtractedUrlInfo>>();
+
+        if options.include_quoted {
+            if let Some(quoted) = tweet.quoted_tweet() {
+                trace!(quoted_id = ?quoted.id(), "Adding quoted tweet media");
+
+                urls.extend(
+                    get_tweet_media_urls(&quoted)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(Into::<ExtractedUrlInfo>::into),
+                );
+            }
+        }
+
+        if options.screenshot_each_tweet {
+            let username = tweet.author_username().unwrap_or(fallback_username);
+
+            if let (Some(id), false) = (tweet.id(), username == "i") {
+                let tweet_url = format!("https://x.com/{}/status/{}", username, id);
+
+                trace!(?tweet_url, "Adding Tweet screenshot URL");
+                urls.push(self.screenshot_tweet_url_info(&tweet_url));
+            }
+        }
+
+        urls
+    }
+}
+
+const DEFAULT_MAX_THREAD_LENGTH: usize = 20;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct TwitterExtractorOptions {
+    /// Also get the media of the tweet that is being quoted
+    include_quoted: bool,
+    /// Walk up the thread while the tweets are replies by the same author
+    include_thread: bool,
+    /// Maximum number of tweets to get when walking the thread
+    max_thread_length: Option<usize>,
+    /// Screenshot every tweet instead of only the requested one
+    screenshot_each_tweet: bool,
+}
+impl TwitterExtractorOptions {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub const fn with_include_quoted(mut self, include_quoted: bool) -> Self {
+        self.include_quoted = include_quoted;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_include_thread(mut self, include_thread: bool) -> Self {
+        self.include_thread = include_thread;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_max_thread_length(mut self, max_thread_length: Option<usize>) -> Self {
+        self.max_thread_length = max_thread_length;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_screenshot_each_tweet(mut self, screenshot_each_tweet: bool) -> Self {
+        self.screenshot_each_tweet = screenshot_each_tweet;
+        self
+    }
+
+    fn max_thread_length(&self) -> usize {
+        self.max_thread_length
+            .unwrap_or(DEFAULT_MAX_THREAD_LENGTH)
+            .max(1)
+    }
+}
+impl From<TwitterExtractorOptions> for ExtractorOptions {
+    fn from(val: TwitterExtractorOptions) -> Self {
+        let val = serde_json::to_value(val)
+            .ok()
+            .and_then(|x| x.as_object().cloned())
+            .expect("Failed to serialize options");
+
+        val.into_iter().collect()
+    }
 }
 
 #[derive(Debug)]
@@ -151,11 +278,149 @@ fn get_tweet_info_from_url(url: &str) -> Result<Option<TweetInfo>, String> {
 
 #[derive(Debug)]
 struct TweetData(serde_json::Value);
+impl TweetData {
+    /// The actual tweet object.
+    /// Some tweets are wrapped in a `TweetWithVisibilityResults` object.
+    fn tweet(&self) -> &serde_json::Value {
+        self.0.get("tweet").unwrap_or(&self.0)
+    }
 
-#[tracing::instrument]
-async fn get_tweet_data(tweet_id: &str) -> Result<TweetData, String> {
-    let guest_auth = get_guest_auth().await?;
+    fn legacy_str(&self, key: &str) -> Option<&str> {
+        self.tweet()
+            .get("legacy")
+            .and_then(|x| x.get(key))
+            .and_then(serde_json::Value::as_str)
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.tweet()
+            .get("rest_id")
+            .and_then(serde_json::Value::as_str)
+            .or_else(|| self.legacy_str("id_str"))
+    }
+
+    fn legacy_u64(&self, key: &str) -> Option<u64> {
+        self.tweet()
+            .get("legacy")
+            .and_then(|x| x.get(key))
+            .and_then(serde_json::Value::as_u64)
+    }
+
+    /// Newer responses have the user fields in `core`, older ones in `legacy`.
+    fn author_str(&self, key: &str) -> Option<&str> {
+        let user = self
+            .tweet()
+            .get("core")
+            .and_then(|x| x.get("user_results"))
+            .and_then(|x| x.get("result"))?;
+
+        user.get("core")
+            .and_then(|x| x.get(key))
+            .or_else(|| user.get("legacy").and_then(|x| x.get(key)))
+            .and_then(serde_json::Value::as_str)
+    }
+
+    fn author_username(&self) -> Option<&str> {
+        self.author_str("screen_name")
+    }
+
+    /// Long tweets have their full text in a separate note tweet.
+    fn text(&self) -> Option<&str> {
+        self.tweet()
+            .get("note_tweet")
+            .and_then(|x| x.get("note_tweet_results"))
+            .and_then(|x| x.get("result"))
+            .and_then(|x| x.get("text"))
+            .and_then(serde_json::Value::as_str)
+            .or_else(|| self.legacy_str("full_text"))
+    }
+
+    fn created_at(&self) -> Option<DateTime<Utc>> {
+        self.legacy_str("created_at")
+            .and_then(|x| DateTime::parse_from_str(x, "%a %b %d %H:%M:%S %z %Y").ok())
+            .map(|x| x.with_timezone(&Utc))
+    }
+
+    fn views(&self) -> Option<u64> {
+        self.tweet()
+            .get("views")
+            .and_then(|x| x.get("count"))
+            .and_then(serde_json::Value::as_str)
+            .and_then(|x| x.parse().ok())
+    }
+
+    fn post_metadata(&self, source_url: &str) -> PostMetadata {
+        PostMetadata::new()
+            .with_author_handle(self.author_username())
+            .with_author_display_name(self.author_str("name"))
+            .with_text(self.text())
+            .with_created_at(self.created_at())
+            .with_source_url(Some(source_url))
+            .with_engagement(PostEngagement {
+                likes: self.legacy_u64("favorite_count"),
+                reposts: self.legacy_u64("retweet_count"),
+                replies: self.legacy_u64("reply_count"),
+                quotes: self.legacy_u64("quote_count"),
+                views: self.views(),
+            })
+    }
+
+    /// The id of the tweet this one is replying to,
+    /// but only if the author is replying to themselves.
+    fn self_reply_parent_id(&self) -> Option<&str> {
+        let author_id = self.legacy_str("user_id_str")?;
+        let reply_to_user_id = self.legacy_str("in_reply_to_user_id_str")?;
+
+        if author_id != reply_to_user_id {
+            return None;
+        }
+
+        self.legacy_str("in_reply_to_status_id_str")
+    }
+
+    fn quoted_tweet(&self) -> Option<Self> {
+        self.tweet()
+            .get("quoted_status_result")
+            .and_then(|x| x.get("result"))
+            .map(|x| Self(x.clone()))
+    }
+}
+
+#[tracing::instrument(skip(guest_auth, tweet))]
+async fn get_thread_tweets(
+    guest_auth: &GuestAuth,
+    tweet: TweetData,
+    max_length: usize,
+) -> Vec<TweetData> {
+    let mut thread = vec![tweet];
+
+    while thread.len() < max_length {
+        let Some(parent_id) = thread
+            .last()
+            .and_then(TweetData::self_reply_parent_id)
+            .map(ToString::to_string)
+        else {
+            break;
+        };
+
+        trace!(?parent_id, "Getting parent tweet of thread");
+
+        match get_tweet_data(guest_auth, &parent_id).await {
+            Ok(x) => thread.push(x),
+            Err(e) => {
+                warn!(?e, ?parent_id, "Failed to get parent tweet of thread");
+                break;
+            }
+        }
+    }
+
+    thread.reverse();
+
+    thread
+}
 
+#[tracing::instrument(skip(guest_auth))]
+async fn get_tweet_data(guest_auth: &GuestAuth, tweet_id: &str) -> Result<TweetData, String> {
     let query_params = {
         let graphql_variables = json!({
             "tweetId": tweet_id,
@@ -321,7 +586,7 @@ fn get_tweet_media_urls(tweet_data: &TweetData) -> Option<Vec<TweetMedia>> {
     trace!(?tweet_data, "Getting tweet media from tweet data");
 
     let mut tweet_media = tweet_data
-        .0
+        .tweet()
         .get("legacy")
         .and_then(|x| x.as_object())
         .and_then(|x| x.get("extended_entities"))
@@ -1,8 +1,14 @@
 pub use common::{
-    extract_info_request::ExtractInfoRequest,
+    extract_info_request::{ExtractInfoRequest, ExtractorOptions},
     extracted_info::{ExtractedInfo, ExtractedUrlInfo},
+    post_metadata::{PostEngagement, PostMetadata},
 };
 pub use handlers::AVAILABLE_EXTRACTORS;
+use tracing::debug;
+
+/// Set by extractors in [`ExtractedInfo::meta`] to have the downloaded images and
+/// audio track (the last URL) rendered into a video
+pub const RENDER_SLIDESHOW_META_KEY: &str = "render-slideshow";
 
 mod common;
 pub mod handlers;
@@ -22,13 +28,31 @@ pub trait Extractor {
 }
 
 pub async fn extract_info(request: &ExtractInfoRequest) -> Result<ExtractedInfo, String> {
+    let (request, redirect_chain) = common::short_link::resolve_request(request).await?;
+
+    if !redirect_chain.is_empty() {
+        debug!(url = ?request.url.as_str(), "Resolved request URL");
+    }
+
     for extractor in AVAILABLE_EXTRACTORS.iter() {
-        if extractor.can_handle(request).await {
-            return extractor.extract_info(request).await.map(|x| {
-                x.with_meta(
+        if extractor.can_handle(&request).await {
+            return extractor.extract_info(&request).await.map(|x| {
+                let x = x.with_extractor(extractor.name()).with_meta(
                     "extractor",
                     serde_json::to_value(extractor).expect("Failed to serialize extractor"),
-                )
+                );
+
+                if redirect_chain.is_empty() {
+                    x
+                } else {
+                    x.with_meta(
+                        "redirect_chain",
+                        redirect_chain
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>(),
+                    )
+                }
                 .dedup_urls()
             });
         }
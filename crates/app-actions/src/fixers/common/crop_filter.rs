@@ -119,6 +119,7 @@ async fn generate_crop_filter_for_files(
             .imagemagick_path()
             .expect("Imagemagick not found"),
     );
+    cmd.kill_on_drop(true);
     let res = {
         let mut res = cmd.args(files);
         if let Some(filter) = initial_filter {
@@ -8,11 +8,24 @@ use super::fix_request::FixRequest;
 pub struct FixResult {
     pub request: FixRequest,
     pub file_path: PathBuf,
+    /// Names of the fixers that ran successfully on the file
+    #[serde(default)]
+    pub applied_fixers: Vec<String>,
 }
 
 impl FixResult {
     #[must_use]
     pub const fn new(request: FixRequest, file_path: PathBuf) -> Self {
-        Self { request, file_path }
+        Self {
+            request,
+            file_path,
+            applied_fixers: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_applied_fixers(mut self, applied_fixers: Vec<String>) -> Self {
+        self.applied_fixers = applied_fixers;
+        self
     }
 }
@@ -62,6 +62,7 @@ impl Fixer for CropImage {
                     .imagemagick_path()
                     .expect("Imagemagick not found"),
             );
+            cmd.kill_on_drop(true);
             cmd.arg(input_file_path);
             cmd.arg("-crop")
                 .arg(crop_filter.to_imagemagick_dimensions());
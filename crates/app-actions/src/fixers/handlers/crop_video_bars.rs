@@ -126,6 +126,7 @@ async fn do_auto_crop_video(file_path: &Path) -> Result<PathBuf, CropError> {
     trace!(?new_filename, "Using new filename for file");
 
     let mut cmd = Command::new(ActionsConfig::dependency_paths().ffmpeg_path());
+    cmd.kill_on_drop(true);
     let res = cmd
         .arg("-y")
         .args(["-loglevel", "panic"])
@@ -166,6 +167,7 @@ async fn generate_crop_filter(file_path: &Path) -> Result<CropFilter, CropError>
     trace!(?tmp_dir, "Created temp dir to write frames to");
 
     let mut cmd = Command::new(ActionsConfig::dependency_paths().ffmpeg_path());
+    cmd.kill_on_drop(true);
     let res = cmd
         .arg("-y")
         .arg("-i")
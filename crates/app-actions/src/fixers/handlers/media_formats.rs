@@ -208,6 +208,7 @@ async fn transcode_media_into(
     let ffmpeg_path = ActionsConfig::dependency_paths().ffmpeg_path();
     trace!("`ffmpeg' binary: {ffmpeg_path:?}");
     let mut cmd = Command::new(ffmpeg_path);
+    cmd.kill_on_drop(true);
     let mut cmd = cmd
         .arg("-y")
         .arg("-hide_banner")
@@ -59,6 +59,7 @@ pub async fn fix_file_with(fixers: Vec<FixerInstance>, request: FixRequest) -> F
     let transfer_file_times = transferable_file_times(&request.file_path);
 
     let mut req = request.clone();
+    let mut applied_fixers = vec![];
     for fixer in fixers {
         trace!(?fixer, "Trying fixer");
 
@@ -78,6 +79,7 @@ pub async fn fix_file_with(fixers: Vec<FixerInstance>, request: FixRequest) -> F
 
         trace!(?result, "Fixer result");
 
+        applied_fixers.push(fixer.name().to_string());
         req = req.clone_with_path(result.file_path);
     }
 
@@ -91,5 +93,5 @@ pub async fn fix_file_with(fixers: Vec<FixerInstance>, request: FixRequest) -> F
 
     debug!(?req, "Fixed file");
 
-    Ok(FixResult::new(request.clone(), req.file_path))
+    Ok(FixResult::new(request.clone(), req.file_path).with_applied_fixers(applied_fixers))
 }
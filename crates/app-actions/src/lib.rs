@@ -1,16 +1,25 @@
 use std::path::Path;
 
-use futures::{stream::FuturesUnordered, StreamExt};
+use actions::{
+    handlers::render_slideshow::RenderSlideshow, Action, ActionRequest, ActionResultData,
+};
+use extractors::RENDER_SLIDESHOW_META_KEY;
+use futures::future::join_all;
 use tracing::{debug, info_span, Instrument};
 
 pub mod actions;
+pub mod canonical_url;
 pub(crate) mod common;
 pub mod config;
 pub mod downloaders;
+pub mod explain;
 pub mod extractors;
 pub mod fixers;
 
-pub async fn download_file<R>(request: R, download_dir: &Path) -> Vec<downloaders::DownloaderReturn>
+pub async fn download_file<R>(
+    request: R,
+    download_dir: &Path,
+) -> Vec<Result<downloaders::DownloadResult, downloaders::DownloaderError>>
 where
     R: Into<extractors::ExtractInfoRequest> + Send + Sync + std::fmt::Debug,
 {
@@ -37,12 +46,23 @@ where
 
         debug!(?download_requests, "Download requests");
 
-        let download_results = download_requests
-            .into_iter()
-            .map(|x| async move { downloaders::download_file(&x).await })
-            .collect::<FuturesUnordered<_>>()
-            .collect::<Vec<_>>()
-            .await;
+        // Keeps the results in the order the extractor returned the URLs in
+        let mut download_results = join_all(
+            download_requests
+                .into_iter()
+                .map(|x| async move { downloaders::download_file(&x).await }),
+        )
+        .await
+        .into_iter()
+        .flat_map(|x| match x {
+            Ok(results) => results.into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        })
+        .collect::<Vec<_>>();
+
+        if info.meta.get(RENDER_SLIDESHOW_META_KEY) == Some(&serde_json::Value::Bool(true)) {
+            download_results.extend(render_slideshow(&download_results).await);
+        }
 
         debug!(?download_results, "Download results");
 
@@ -52,6 +72,53 @@ where
     .await
 }
 
+/// Renders the downloaded slideshow images and the audio track (the last file) into a video.
+///
+/// Only runs if every file of the slideshow was downloaded.
+async fn render_slideshow(
+    results: &[Result<downloaders::DownloadResult, downloaders::DownloaderError>],
+) -> Option<Result<downloaders::DownloadResult, downloaders::DownloaderError>> {
+    let files = results
+        .iter()
+        .map(|x| x.as_ref().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let (audio, images) = files.split_last()?;
+    if images.is_empty() {
+        return None;
+    }
+
+    let images = images.iter().map(|x| x.path.clone()).collect::<Vec<_>>();
+    let request = ActionRequest::in_same_dir(audio.path.clone())?
+        .with_option("images", serde_json::to_value(images).ok()?);
+
+    if !RenderSlideshow.can_run_for(&request).await {
+        debug!(?request, "Can't render slideshow");
+        return None;
+    }
+
+    let result = match RenderSlideshow.run(&request).await {
+        Ok(x) => x,
+        Err(e) => return Some(Err(format!("Failed to render slideshow: {e:?}"))),
+    };
+
+    let ActionResultData::Paths(paths) = result.data else {
+        return Some(Err("Slideshow render did not produce a file".to_string()));
+    };
+
+    Some(
+        paths
+            .into_iter()
+            .next()
+            .map(|path| downloaders::DownloadResult {
+                request: audio.request.clone(),
+                path,
+                media_info: None,
+                downloader: None,
+            })
+            .ok_or_else(|| "Slideshow render did not produce a file".to_string()),
+    )
+}
+
 pub async fn fix_file<R>(request: R) -> fixers::FixerReturn
 where
     R: Into<fixers::FixRequest> + Send + Sync + std::fmt::Debug,
@@ -0,0 +1,25 @@
+use std::path::PathBuf;
+
+use clap::{Args, ValueHint};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::validators::directory::{validate_is_directory, value_parser_parse_valid_directory};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Args, Validate)]
+#[clap(next_help_heading = Some("Credentials"))]
+pub struct CredentialsConfig {
+    /// Directory with credentials that are added to requests based on their domain.
+    ///
+    /// Files ending in `.headers` are named after the domain they apply to (eg. `reddit.com.headers`)
+    /// and contain one `Name: value` header per line (eg. `Authorization: Bearer <token>`).
+    /// All other files are read as Netscape cookie files (eg. exported from a browser).
+    /// Lines starting with `#` are ignored.
+    /// Credentials also apply to subdomains of the domain.
+    ///
+    /// Only the cookies are passed to yt-dlp since it would send the headers to every host it
+    /// requests. Headers given with a request are passed to yt-dlp as they are.
+    #[arg(long, env = "DOWNLOADER_HUB_CREDENTIALS_DIR", value_hint = ValueHint::DirPath, value_parser = value_parser_parse_valid_directory())]
+    #[validate(custom(function = "validate_is_directory"))]
+    pub credentials_dir: Option<PathBuf>,
+}
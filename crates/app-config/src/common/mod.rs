@@ -1,9 +1,13 @@
+pub mod credentials;
 pub mod endpoint;
 pub mod program_path;
 pub mod project;
+pub mod proxy;
 pub mod task;
 
+pub use credentials::*;
 pub use endpoint::*;
 pub use program_path::*;
 pub use project::*;
+pub use proxy::*;
 pub use task::*;
@@ -0,0 +1,98 @@
+use clap::{Args, ValueHint};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use validator::Validate;
+
+const PROXY_SCHEMES: &[&str] = &["http", "https", "socks5", "socks5h"];
+const DIRECT: &str = "direct";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Args, Validate)]
+#[clap(next_help_heading = Some("Proxy"))]
+pub struct ProxyConfig {
+    /// The proxy to send outgoing requests through.
+    ///
+    /// Supports `http://`, `https://`, `socks5://` and `socks5h://` URLs.
+    /// If not set, requests are sent directly unless a proxy rule matches.
+    #[arg(long, env = "DOWNLOADER_HUB_PROXY", value_hint = ValueHint::Url, value_parser = parse_proxy_url)]
+    pub proxy: Option<Url>,
+
+    /// Route requests to some domains through a different proxy.
+    ///
+    /// Format: `<domain>[,<domain>...]=<proxy URL or "direct">`.
+    /// Rules also apply to subdomains and the first matching rule wins.
+    /// Eg. `tiktok.com,instagram.com=socks5://127.0.0.1:1080` or `example.com=direct`
+    #[arg(long = "proxy-rule", env = "DOWNLOADER_HUB_PROXY_RULES", value_delimiter = ';', value_parser = ProxyRule::parse_str)]
+    pub proxy_rules: Vec<ProxyRule>,
+}
+impl ProxyConfig {
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.proxy.is_some() || !self.proxy_rules.is_empty()
+    }
+
+    /// The proxy for requests to the host or `None` if they should be sent directly
+    #[must_use]
+    pub fn proxy_for(&self, host: &str) -> Option<&Url> {
+        self.proxy_rules
+            .iter()
+            .find(|x| x.matches(host))
+            .map_or(self.proxy.as_ref(), |x| x.proxy.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyRule {
+    pub domains: Vec<String>,
+    /// `None` means the requests are sent directly
+    pub proxy: Option<Url>,
+}
+impl ProxyRule {
+    pub fn parse_str(arg: &str) -> Result<Self, String> {
+        let (domains, proxy) = arg
+            .split_once('=')
+            .ok_or_else(|| format!("invalid proxy rule (missing `=`): {arg}"))?;
+
+        let domains = domains
+            .split(',')
+            .map(|x| x.trim().trim_start_matches('.').to_lowercase())
+            .filter(|x| !x.is_empty())
+            .collect::<Vec<_>>();
+
+        if domains.is_empty() {
+            return Err(format!("invalid proxy rule (no domains): {arg}"));
+        }
+
+        let proxy = match proxy.trim() {
+            x if x.eq_ignore_ascii_case(DIRECT) => None,
+            x => Some(parse_proxy_url(x)?),
+        };
+
+        Ok(Self { domains, proxy })
+    }
+
+    #[must_use]
+    pub fn matches(&self, host: &str) -> bool {
+        let host = host.to_lowercase();
+
+        self.domains.iter().any(|domain| {
+            host == *domain
+                || host
+                    .strip_suffix(domain.as_str())
+                    .is_some_and(|x| x.ends_with('.'))
+        })
+    }
+}
+
+fn parse_proxy_url(arg: &str) -> Result<Url, String> {
+    let url = Url::parse(arg.trim()).map_err(|e| format!("invalid proxy URL: {e}"))?;
+
+    if !PROXY_SCHEMES.contains(&url.scheme()) {
+        return Err(format!(
+            "invalid proxy URL (scheme must be one of {}): {}",
+            PROXY_SCHEMES.join(", "),
+            url.scheme()
+        ));
+    }
+
+    Ok(url)
+}
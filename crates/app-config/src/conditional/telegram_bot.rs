@@ -48,6 +48,12 @@ pub struct TelegramBotConfig {
     /// If left empty, a generic default text will be used.
     #[arg(long = "telegram-about", value_name = "ABOUT", env = "DOWNLOADER_HUB_TELEGRAM_ABOUT", value_hint = ValueHint::Other)]
     pub about: Option<String>,
+
+    /// Caption uploaded files with info about the post they were downloaded from.
+    ///
+    /// Includes the author, post text, date and link when the extractor provides them.
+    #[arg(long = "telegram-caption-with-post-metadata", env = "DOWNLOADER_HUB_TELEGRAM_CAPTION_WITH_POST_METADATA", action = clap::ArgAction::SetTrue)]
+    pub caption_with_post_metadata: bool,
 }
 impl TelegramBotConfig {
     #[must_use]
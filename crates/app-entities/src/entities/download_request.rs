@@ -19,9 +19,13 @@ pub struct Model {
     pub client_id: i32,
     #[sea_orm(column_type = "Text")]
     pub url: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub canonical_url: Option<String>,
     pub status: ItemStatus,
     #[sea_orm(column_type = "JsonBinary")]
     pub meta: Json,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub post_metadata: Option<Json>,
     #[sea_orm(column_name = "_app_meta", column_type = "JsonBinary")]
     #[serde(skip)]
     pub app_meta: Json,
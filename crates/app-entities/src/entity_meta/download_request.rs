@@ -38,6 +38,10 @@ pub struct DownloadRequestMeta {
     #[serde(default)]
     pub skip_fixing: bool,
     #[serde(default)]
+    pub extractor_options: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub downloader_options: HashMap<String, serde_json::Value>,
+    #[serde(default)]
     pub other: HashMap<String, serde_json::Value>,
 }
 impl From<DownloadRequestMeta> for serde_json::Value {
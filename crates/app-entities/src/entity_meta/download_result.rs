@@ -3,15 +3,30 @@ use serde::{Deserialize, Serialize};
 use super::common::path::AppPath;
 use crate::{download_result, sea_orm_active_enums::ItemStatus};
 
+/// Key in the result meta under which the downloader provided media info is stored
+pub const MEDIA_INFO_META_KEY: &str = "mediaInfo";
+/// Key in the result meta under which the extractor and downloader of the file are stored
+pub const SOURCE_META_KEY: &str = "source";
+
 impl download_result::Model {
     #[must_use]
     pub fn path(&self) -> Option<AppPath> {
         self.path.clone().and_then(|x| AppPath::try_from(x).ok())
     }
 
+    /// The meta object can also hold other keys (eg. the media info)
+    /// so only the first key that is a valid meta variant is parsed.
     #[must_use]
     pub fn meta(&self) -> Option<DownloadResultMeta> {
-        serde_json::from_value(self.meta.clone()).ok()
+        self.meta
+            .as_object()?
+            .iter()
+            .find_map(|(k, v)| serde_json::from_value(serde_json::json!({ k: v })).ok())
+    }
+
+    #[must_use]
+    pub fn media_info(&self) -> Option<&serde_json::Value> {
+        self.meta.get(MEDIA_INFO_META_KEY).filter(|x| !x.is_null())
     }
 }
 
@@ -41,6 +56,13 @@ impl From<DownloadResultStatus> for ItemStatus {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadResultSource {
+    pub extractor: Option<String>,
+    pub downloader: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum DownloadResultMeta {
@@ -37,6 +37,7 @@ where
 
     let ffprobe_path = HelpersConfig::dependency_paths().ffprobe_path();
     let mut cmd = Command::new(ffprobe_path);
+    cmd.kill_on_drop(true);
     {
         cmd.args(["-v", "quiet"])
             .args(["-print_format", "json=c=1"])
@@ -5,12 +5,18 @@ pub const DEBUG: bool = true;
 #[macro_use]
 pub mod common;
 mod m20220101_000001_create_table;
+mod m20261018_000001_add_canonical_url;
+mod m20261018_000002_add_post_metadata;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_create_table::Migration)]
+        vec![
+            Box::new(m20220101_000001_create_table::Migration),
+            Box::new(m20261018_000001_add_canonical_url::Migration),
+            Box::new(m20261018_000002_add_post_metadata::Migration),
+        ]
     }
 }
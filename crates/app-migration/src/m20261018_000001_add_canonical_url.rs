@@ -0,0 +1,47 @@
+use sea_orm_migration::prelude::*;
+
+use crate::common::generate_index;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        {
+            let stmt = Table::alter()
+                .table(DownloadRequest::Table)
+                .add_column_if_not_exists(
+                    ColumnDef::new(DownloadRequest::CanonicalUrl).text().null(),
+                )
+                .to_owned();
+            debug_print!(stmt.to_string(PostgresQueryBuilder));
+            manager.alter_table(stmt).await?;
+
+            let stmt = generate_index(DownloadRequest::Table, vec![DownloadRequest::CanonicalUrl]);
+            debug_print!(stmt.to_string(PostgresQueryBuilder));
+            manager.create_index(stmt).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(DownloadRequest::Table)
+                    .drop_column(DownloadRequest::CanonicalUrl)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum DownloadRequest {
+    Table,
+    CanonicalUrl,
+}
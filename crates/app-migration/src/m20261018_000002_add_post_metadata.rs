@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        {
+            let stmt = Table::alter()
+                .table(DownloadRequest::Table)
+                .add_column_if_not_exists(
+                    ColumnDef::new(DownloadRequest::PostMetadata)
+                        .json_binary()
+                        .null(),
+                )
+                .to_owned();
+            debug_print!(stmt.to_string(PostgresQueryBuilder));
+            manager.alter_table(stmt).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(DownloadRequest::Table)
+                    .drop_column(DownloadRequest::PostMetadata)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum DownloadRequest {
+    Table,
+    PostMetadata,
+}